@@ -2,78 +2,177 @@
 //! using a bech32 encoding of the public key
 
 use bech32::{self, FromBase32, ToBase32};
-use ed25519_dalek::{PublicKey, SecretKey, Keypair, Signer};
-use super::{Result, ElrondClientError};
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use ed25519_dalek::{PublicKey, SecretKey, Keypair, Signer as _, SECRET_KEY_LENGTH};
+use zeroize::Zeroizing;
+use super::{Signer, Result, ElrondClientError};
+use super::message::message_hash;
 use rand::rngs::OsRng;
 
-/// Representation for an address on the Elrond network. Addresses on Elrond are derived from
-/// public keys on the ed25519 curve, encoded with the Bech32 format originally created for 
-/// segwit on Bitcoin in BIP 0173.
-#[derive(Clone, Debug, PartialEq)]
-pub struct ElrondAddress {
-    inner: String
-}
+/// Human-readable part of bech32 addresses on the Elrond network
+pub const DEFAULT_HRP: &str = "erd";
 
-/// Decode a `bech32` encoded value and return an ed25519 public key if it is a valid elrond address
-fn check_elrond_address(addr_str: &str) -> Result<Option<PublicKey>> {
-    let (hrd, data) = bech32::decode(addr_str).map_err(|_| {
-        ElrondClientError::new("could not decode address from string")
-    })?;
-    if hrd == "erd" {
-        let public_key_bytes = Vec::<u8>::from_base32(&data).map_err(|_| {
-            ElrondClientError::new("could not convert base32 to bytes")
-        })?;
-        let public_key = PublicKey::from_bytes(&public_key_bytes).map_err(|_| {
-            ElrondClientError::new("bytes in bech32 are not a valid public key")
-        })?;
-        Ok(Some(public_key))
-    } else {
-        Ok(None)
-    }
+/// Length in bytes of an address
+pub const ADDRESS_LENGTH: usize = 32;
+
+/// Representation for an address on the Elrond network. Addresses on Elrond are 32 byte values,
+/// encoded with the Bech32 format originally created for segwit on Bitcoin in BIP 0173. The
+/// addresses of accounts are ed25519 public keys, whereas smart contract and system addresses
+/// need not be valid curve points. The human-readable part is "erd" on Elrond: parsing (`new`,
+/// `FromStr`, `Deserialize`) only accepts "erd" addresses, which are the only ones the client and
+/// transactions work with. `from_bech32` and `with_hrp` convert to and from the human-readable
+/// parts of forks and sidechains.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ElrondAddress {
+    hrp: Cow<'static, str>,
+    bytes: [u8; ADDRESS_LENGTH]
 }
 
 impl ElrondAddress {
-    /// Create a new `ElrondAddress` from a string value. This will check validity.
+    /// Staking system smart contract, keeping track of the nodes of validators
+    pub const STAKING_SC: Self = Self::system_sc(0);
+    /// Validator system smart contract, receiving the stake of validators
+    pub const VALIDATOR_SC: Self = Self::system_sc(1);
+    /// ESDT system smart contract, issuing and managing tokens
+    pub const ESDT_SC: Self = Self::system_sc(2);
+    /// Governance system smart contract, where proposals are voted on
+    pub const GOVERNANCE_SC: Self = Self::system_sc(3);
+    /// Delegation manager system smart contract, creating staking provider contracts
+    pub const DELEGATION_MANAGER_SC: Self = Self::system_sc(4);
+
+    /// Address of a system smart contract on the metachain
+    const fn system_sc(id: u8) -> Self {
+        let mut bytes = [0u8; ADDRESS_LENGTH];
+        bytes[9] = 1;
+        bytes[29] = id;
+        bytes[30] = 0xff;
+        bytes[31] = 0xff;
+        Self { hrp: Cow::Borrowed(DEFAULT_HRP), bytes }
+    }
+    /// Create a new `ElrondAddress` from a bech32 string with the "erd" human-readable part.
+    /// This will check validity.
     pub fn new(addr_str: &str) -> Result<Self> {
-        // verify if address is valid first
-        if let Some(_pk) = check_elrond_address(addr_str)? {
-            Ok(Self { inner: addr_str.to_string() })
-        } 
-        else {
-            Err(ElrondClientError::new(
+        let address = Self::from_bech32(addr_str)?;
+        if address.hrp != DEFAULT_HRP {
+            return Err(ElrondClientError::new(
                 &format!(
                     "'{}' is not a valid elrond address",
                     addr_str
                 )
-            ))
+            ));
         }
+        Ok(address)
+    }
+    /// Create a new `ElrondAddress` from a bech32 string with any human-readable part, e.g., to
+    /// convert the address of a fork or sidechain with `with_hrp(DEFAULT_HRP)`
+    pub fn from_bech32(addr_str: &str) -> Result<Self> {
+        let (hrp, data) = bech32::decode(addr_str).map_err(|_| {
+            ElrondClientError::new("could not decode address from string")
+        })?;
+        let bytes = Vec::<u8>::from_base32(&data).map_err(|_| {
+            ElrondClientError::new("could not convert base32 to bytes")
+        })?;
+        Self::from_bytes(&bytes)?.with_hrp(&hrp)
+    }
+    /// Create a new `ElrondAddress` from 32 bytes, e.g., a public key
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != ADDRESS_LENGTH {
+            return Err(ElrondClientError::new(
+                &format!("address must be {} bytes long, got {}", ADDRESS_LENGTH, bytes.len())
+            ));
+        }
+        let mut address = [0u8; ADDRESS_LENGTH];
+        address.copy_from_slice(bytes);
+        Ok(Self { hrp: Cow::Borrowed(DEFAULT_HRP), bytes: address })
+    }
+    /// Create a new `ElrondAddress` from the hex encoding of its 32 bytes
+    pub fn from_hex(hex_str: &str) -> Result<Self> {
+        let bytes = hex::decode(hex_str).map_err(|_| {
+            ElrondClientError::new("could not decode hex string")
+        })?;
+        Self::from_bytes(&bytes)
     }
-    /// Covert `ElrondAddress` to a public key
-    pub fn to_public_key(&self) -> PublicKey {
-        // this is safe as the only way to modify an inner value is via "new", which checks validity
-        check_elrond_address(&self.inner)
-            .expect("inner valid of elrond address corrupted (encoding)")
-            .expect("inner valid of elrond address corrupted (not elrond address)")
+    /// Same address, encoded with another human-readable part
+    pub fn with_hrp(mut self, hrp: &str) -> Result<Self> {
+        // encoding fails on invalid human-readable parts
+        bech32::encode(hrp, self.bytes.to_base32()).map_err(|_| {
+            ElrondClientError::new(&format!("'{}' is not a valid bech32 human-readable part", hrp))
+        })?;
+        self.hrp = Cow::Owned(hrp.to_lowercase());
+        Ok(self)
+    }
+    /// Human-readable part of the bech32 encoding, "erd" on Elrond
+    pub fn hrp(&self) -> &str {
+        &self.hrp
+    }
+    /// Raw bytes of the address
+    pub fn to_bytes(&self) -> [u8; ADDRESS_LENGTH] {
+        self.bytes
+    }
+    /// Hex encoding of the address
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.bytes)
+    }
+    /// Bech32 encoding of the address, also available through `Display`
+    pub fn to_bech32(&self) -> String {
+        bech32::encode(&self.hrp, self.bytes.to_base32())
+            .expect("human-readable part checked when creating the address")
+    }
+    /// Whether the address belongs to a smart contract (including system smart contracts),
+    /// recognizable by its 8 leading zero bytes
+    pub fn is_smart_contract(&self) -> bool {
+        self.bytes[..8].iter().all(|byte| *byte == 0)
+    }
+    /// Covert `ElrondAddress` to a public key, failing for addresses that are not valid points on
+    /// the ed25519 curve, such as most smart contract addresses
+    pub fn to_public_key(&self) -> Result<PublicKey> {
+        PublicKey::from_bytes(&self.bytes).map_err(|_| {
+            ElrondClientError::new(&format!("'{}' is not a valid public key", self))
+        })
     }
     /// Create a new `ElrondAddress` from a ed25519 public key
     pub fn from_public_key(public_key: &PublicKey) -> Result<Self> {
-        let inner = bech32::encode("erd", public_key.to_base32()).map_err(|_| {
-            ElrondClientError::new("could not encode public key as bech32")
-        })?;
-        Ok(Self { inner })
+        Self::from_bytes(public_key.as_bytes())
+    }
+}
+
+impl fmt::Display for ElrondAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_bech32())
+    }
+}
+
+impl FromStr for ElrondAddress {
+    type Err = ElrondClientError;
+    fn from_str(addr_str: &str) -> Result<Self> {
+        Self::new(addr_str)
     }
-    /// Get string representation of address
-    pub fn to_string(&self) -> String {
-        self.inner.clone()
+}
+
+impl Serialize for ElrondAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_bech32())
+    }
+}
+
+impl<'de> Deserialize<'de> for ElrondAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let addr_str = String::deserialize(deserializer)?;
+        Self::new(&addr_str).map_err(serde::de::Error::custom)
     }
 }
 
 /// An account is derived from a ed25519 keypair. New transactions are signed with the secret key and
 /// signatures can be verified with the public key. An address derived from the public key (in Bech32 
-// format) may be the recipient of other transactions.
+/// format) may be the recipient of other transactions.
+///
+/// The keypair is kept private and its secret key is zeroized when the account is dropped. The
+/// secret key can only be read through the `export_*` methods.
 pub struct Account {
-    pub secret: SecretKey,
-    pub public: PublicKey,
+    keypair: Keypair,
     pub address: ElrondAddress
 }
 
@@ -81,50 +180,49 @@ impl Account {
     /// Generate a new Elrond account
     pub fn generate() -> Result<Self> {
         let mut csprng = OsRng{};
-        let secret = SecretKey::generate(&mut csprng);
-        let public = (&secret).into();
-        let address = ElrondAddress::from_public_key(&public)?;
-        Ok(Self {
-            secret,
-            public,
-            address
-        })
+        Self::from_secret(SecretKey::generate(&mut csprng))
     }
     /// Import an Elrond account from an existing secret (private key)
     pub fn from_secret(secret: SecretKey) -> Result<Self> {
         let public = (&secret).into();
         let address = ElrondAddress::from_public_key(&public)?;
         Ok(Self {
-            secret,
-            public,
+            keypair: Keypair { secret, public },
             address
         })
     }
-    /// Sign data with account and return signature as a hex string
+    /// Get the public key of the account
+    pub fn public_key(&self) -> PublicKey {
+        self.keypair.public
+    }
+    /// Sign data with account and return signature as a hex string. The raw UTF-8 bytes are
+    /// signed, use `sign_message` for signatures compatible with wallets.
     pub fn sign(&self, data: &str) -> Result<String> {
-        let public_bytes = self.public.to_bytes();
-        let secret_bytes = self.secret.to_bytes();
-        let combined: Vec<u8> = secret_bytes.iter().chain(&public_bytes).map(|x| x.clone()).collect();
-        // ed25519_dalek requires keypair for signing, can't just use secret
-        // so perhaps save keypair and not the individual keys in the struct
-        let keypair = Keypair::from_bytes(&combined).map_err(|_|{
-            ElrondClientError::new("could not load keypair from public and secret key data")
-        })?;
-        let signature = keypair.sign(data.as_bytes()).to_bytes();
-        assert_eq!(signature.len(), 64);
-        Ok(hex::encode(signature.to_vec()))
+        Ok(hex::encode(self.sign_bytes(data.as_bytes())?))
+    }
+    /// Sign an arbitrary message following the wallet standard (see `verify_message`) and return
+    /// the signature as a hex string
+    pub fn sign_message(&self, message: &[u8]) -> Result<String> {
+        Ok(hex::encode(self.sign_bytes(&message_hash(message))?))
+    }
+    /// Export a copy of the secret/private key associated with an account
+    pub fn export_secret_key(&self) -> SecretKey {
+        SecretKey::from_bytes(self.keypair.secret.as_bytes()).expect("secret key has a valid length")
+    }
+    /// Export the bytes of the secret/private key, zeroized once dropped
+    pub fn export_secret_bytes(&self) -> Zeroizing<[u8; SECRET_KEY_LENGTH]> {
+        Zeroizing::new(self.keypair.secret.to_bytes())
     }
-    /// Returns a hex string representation of the secret/private key associated with an account.
+    /// Export a hex string representation of the secret/private key, zeroized once dropped.
     /// You can restore an account from this data using `Account::from_string`.
-    pub fn to_string(&self) -> String {
-        let secret_bytes = self.secret.to_bytes();
-        hex::encode(&secret_bytes.to_vec())
+    pub fn export_secret_hex(&self) -> Zeroizing<String> {
+        Zeroizing::new(hex::encode(&self.export_secret_bytes()[..]))
     }
     /// Load an account from a hex string representation of a secret/private key
     pub fn from_string(hex_str: &str) -> Result<Self> {
-        let bytes = hex::decode(hex_str).map_err(|_| {
+        let bytes = Zeroizing::new(hex::decode(hex_str).map_err(|_| {
             ElrondClientError::new("could not decode hex string")
-        })?;
+        })?);
         let secret = SecretKey::from_bytes(&bytes).map_err(|_| {
             ElrondClientError::new("hex string bytes do not encode valid secret key")
         })?;
@@ -132,17 +230,37 @@ impl Account {
     }
 }
 
+impl fmt::Debug for Account {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Account")
+            .field("address", &self.address.to_string())
+            .field("secret", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Signer for Account {
+    fn address(&self) -> ElrondAddress {
+        self.address.clone()
+    }
+    fn sign_bytes(&self, data: &[u8]) -> Result<Vec<u8>> {
+        Ok(self.keypair.sign(data).to_bytes().to_vec())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Account, ElrondAddress, SecretKey};
+    use std::collections::HashMap;
+    use super::{Account, ElrondAddress, SecretKey, DEFAULT_HRP};
+    use super::super::{UnsignedTransaction, Network};
     #[test]
     fn generate_and_test_account() {
         let account = Account::generate().unwrap();
-        let secret_bytes = &account.secret.to_bytes();
-        let secret_copy = SecretKey::from_bytes(secret_bytes).unwrap();
+        let secret_bytes = account.export_secret_bytes();
+        let secret_copy = SecretKey::from_bytes(&secret_bytes[..]).unwrap();
         let account2 = Account::from_secret(secret_copy).unwrap();
-        assert_eq!(&account.secret.to_bytes(), &account2.secret.to_bytes());
-        assert_eq!(&account.public.to_bytes(), &account2.public.to_bytes());
+        assert_eq!(account.export_secret_bytes(), account2.export_secret_bytes());
+        assert_eq!(&account.public_key().to_bytes(), &account2.public_key().to_bytes());
         assert_eq!(&account.address, &account2.address);
     }
 
@@ -150,11 +268,75 @@ mod tests {
     fn validate_address(){
         let addr_str = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
         let address = ElrondAddress::new(addr_str).unwrap();
-        let public_key = address.to_public_key();
+        let public_key = address.to_public_key().unwrap();
         let address2 = ElrondAddress::from_public_key(&public_key).unwrap();
         assert_eq!(address, address2);
     }
 
+    #[test]
+    fn address_conversions(){
+        let addr_str = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+        let address = ElrondAddress::new(addr_str).unwrap();
+        assert_eq!(ElrondAddress::from_hex(&address.to_hex()).unwrap(), address);
+        assert_eq!(ElrondAddress::from_bytes(&address.to_bytes()).unwrap(), address);
+        assert_eq!(addr_str.parse::<ElrondAddress>().unwrap(), address);
+        assert_eq!(format!("{}", address), addr_str);
+        assert!(ElrondAddress::from_bytes(&[1u8; 31]).is_err());
+        // custom human-readable parts, for forks and sidechains
+        let other = address.clone().with_hrp("test").unwrap();
+        assert!(other.to_string().starts_with("test1"));
+        assert_eq!(other.to_bytes(), address.to_bytes());
+        assert_eq!(ElrondAddress::from_bech32(&other.to_string()).unwrap(), other);
+        assert!(ElrondAddress::new(&other.to_string()).is_err());
+        assert!(other.to_string().parse::<ElrondAddress>().is_err());
+        assert!(serde_json::from_value::<ElrondAddress>(serde_json::json!(other.to_string())).is_err());
+        assert_eq!(ElrondAddress::from_bech32(&other.to_string()).unwrap().with_hrp(DEFAULT_HRP).unwrap(), address);
+        assert_ne!(other, address);
+    }
+
+    #[test]
+    fn contract_addresses_are_valid(){
+        let zero = ElrondAddress::new("erd1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqq6gq4hu").unwrap();
+        assert_eq!(zero.to_bytes(), [0u8; 32]);
+        let esdt = ElrondAddress::new("erd1qqqqqqqqqqqqqqqpqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqzllls8a5w6u").unwrap();
+        assert!(esdt.is_smart_contract());
+        assert!(esdt.to_public_key().is_err());
+        let account = ElrondAddress::new("erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k").unwrap();
+        assert!(!account.is_smart_contract());
+        assert!(account.to_public_key().is_ok());
+        // transactions to contracts are accepted
+        let tx = UnsignedTransaction::new(0, "1", &esdt.to_string(), &account.to_string(), Network::MainNet);
+        assert!(tx.is_ok());
+    }
+
+    #[test]
+    fn system_contract_addresses(){
+        let expected = [
+            (ElrondAddress::STAKING_SC, "erd1qqqqqqqqqqqqqqqpqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqllls0lczs7"),
+            (ElrondAddress::VALIDATOR_SC, "erd1qqqqqqqqqqqqqqqpqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqplllst77y4l"),
+            (ElrondAddress::ESDT_SC, "erd1qqqqqqqqqqqqqqqpqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqzllls8a5w6u"),
+            (ElrondAddress::GOVERNANCE_SC, "erd1qqqqqqqqqqqqqqqpqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqrlllsrujgla"),
+            (ElrondAddress::DELEGATION_MANAGER_SC, "erd1qqqqqqqqqqqqqqqpqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqylllslmq6y6")
+        ];
+        for (address, bech32) in expected.iter() {
+            assert_eq!(address.to_string(), *bech32);
+            assert_eq!(address, &ElrondAddress::new(bech32).unwrap());
+            assert!(address.is_smart_contract());
+        }
+    }
+
+    #[test]
+    fn address_serde_and_map_keys(){
+        let address = ElrondAddress::new("erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k").unwrap();
+        let json = serde_json::to_string(&address).unwrap();
+        assert_eq!(json, "\"erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k\"");
+        assert_eq!(serde_json::from_str::<ElrondAddress>(&json).unwrap(), address);
+        assert!(serde_json::from_str::<ElrondAddress>("\"erd1invalid\"").is_err());
+        let mut balances = HashMap::new();
+        balances.insert(address.clone(), 5);
+        assert_eq!(balances[&address], 5);
+    }
+
     #[test]
     fn signing_completes(){
         let private_key = "a4b36a5d97176618b5a7fcc9228d2fd98ee2f14ddd3d6462ae03e40eb487d15b";
@@ -166,11 +348,10 @@ mod tests {
     #[test]
     fn save_and_load_account(){
         let account = Account::generate().unwrap();
-        let account_as_string = account.to_string();
-        println!("{}", &account_as_string);
+        let account_as_string = account.export_secret_hex();
         let account_copy = Account::from_string(&account_as_string).unwrap();
-        assert_eq!(&account.secret.to_bytes(), &account_copy.secret.to_bytes());
-        assert_eq!(&account.public.to_bytes(), &account_copy.public.to_bytes());
+        assert_eq!(account.export_secret_bytes(), account_copy.export_secret_bytes());
+        assert_eq!(&account.public_key().to_bytes(), &account_copy.public_key().to_bytes());
         assert_eq!(account.address.to_string(), account_copy.address.to_string());
     }
 
@@ -181,4 +362,13 @@ mod tests {
         assert_eq!("erd146apxa83wr7paz3gsg07dhcpg98ascjtpg9p8l8g5rpmg6chhchq9ccvmc", &account.address.to_string());
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn debug_redacts_secret(){
+        let private_key = "a4b36a5d97176618b5a7fcc9228d2fd98ee2f14ddd3d6462ae03e40eb487d15b";
+        let account = Account::from_string(private_key).unwrap();
+        let debug = format!("{:?}", account);
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains(private_key));
+        assert_eq!(account.export_secret_hex().as_str(), private_key);
+    }
+}
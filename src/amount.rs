@@ -0,0 +1,464 @@
+//! Amounts of eGLD and of arbitrary tokens. Amounts are kept as an integer number of base units
+//! (the denominated form used on the blockchain), so no precision is ever lost. eGLD has 18
+//! decimals, whereas ESDT tokens each define their own number of decimals.
+
+use num_bigint::BigUint;
+use serde::{Serialize, Deserialize};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::str::FromStr;
+use super::{Result, ElrondClientError};
+
+/// Amount of a token, e.g., "1.5 WEGLD-bd4d79", stored as an integer number of base units
+/// (here 1500000000000000000) along with the decimals and identifier of the token. Amounts of
+/// different tokens can't be compared or combined.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "TokenAmountRepr", into = "TokenAmountRepr")]
+pub struct TokenAmount {
+    base: BigUint,
+    decimals: u32,
+    identifier: String
+}
+
+/// Serialized form of a `TokenAmount`, with base units as a string so no precision is lost
+#[derive(Serialize, Deserialize)]
+struct TokenAmountRepr {
+    identifier: String,
+    decimals: u32,
+    amount: String
+}
+
+impl From<TokenAmount> for TokenAmountRepr {
+    fn from(amount: TokenAmount) -> Self {
+        Self {
+            amount: amount.base.to_string(),
+            decimals: amount.decimals,
+            identifier: amount.identifier
+        }
+    }
+}
+
+impl TryFrom<TokenAmountRepr> for TokenAmount {
+    type Error = ElrondClientError;
+    fn try_from(repr: TokenAmountRepr) -> Result<Self> {
+        Self::from_base_units(&repr.amount, repr.decimals, &repr.identifier)
+    }
+}
+
+/// Parse a string of decimal digits, rejecting signs, whitespace and other notations
+pub(crate) fn parse_digits(digits: &str) -> Result<BigUint> {
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ElrondClientError::new(
+            &format!("'{}' is not a non-negative integer", digits)
+        ));
+    }
+    // this unwrap is safe, only digits remain
+    Ok(BigUint::from_str(digits).unwrap())
+}
+
+/// Parse a human readable decimal number (e.g., "1.25") into base units with `decimals` decimals.
+/// Negative numbers, scientific notation and digits beyond the allowed precision are rejected.
+pub(crate) fn parse_human(value: &str, decimals: u32) -> Result<BigUint> {
+    let mut parts = value.splitn(2, '.');
+    // this unwrap is safe, splitn always yields a first part
+    let integer = parts.next().unwrap();
+    let fraction = parts.next().unwrap_or("");
+    if value.ends_with('.') {
+        return Err(ElrondClientError::new(&format!("'{}' is not a valid amount", value)));
+    }
+    let integer = parse_digits(integer).map_err(|_| {
+        ElrondClientError::new(&format!("'{}' is not a valid amount", value))
+    })?;
+    if !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ElrondClientError::new(&format!("'{}' is not a valid amount", value)));
+    }
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals as usize {
+        return Err(ElrondClientError::new(
+            &format!("'{}' has more than {} decimals", value, decimals)
+        ));
+    }
+    let padded = format!("{:0<width$}", fraction, width = decimals as usize);
+    let fraction = if padded.is_empty() { BigUint::from(0u32) } else { parse_digits(&padded)? };
+    Ok(integer * BigUint::from(10u32).pow(decimals) + fraction)
+}
+
+/// Format base units with `decimals` decimals, showing exactly `displayed` decimals (truncating
+/// extra digits) or, if `displayed` is `None`, all significant decimals
+pub(crate) fn format_base_units(base: &BigUint, decimals: u32, displayed: Option<usize>) -> String {
+    let digits = format!("{:0>width$}", base.to_string(), width = decimals as usize + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = match displayed {
+        Some(displayed) if displayed <= fraction.len() => fraction[..displayed].to_string(),
+        Some(displayed) => format!("{:0<width$}", fraction, width = displayed),
+        None => fraction.trim_end_matches('0').to_string()
+    };
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+/// Number of decimals of eGLD
+pub(crate) const EGLD_DECIMALS: u32 = 18;
+
+/// eGLD in denominated form, the integer number of base units used on the blockchain.
+/// "1 eGLD" is 10^18 on the blockchain.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ElrondCurrencyAmount {
+    inner: BigUint
+}
+
+impl ElrondCurrencyAmount {
+    /// Create an amount of eGLD from a human input, e.g., "2" for 2 eGLD. Negative values,
+    /// scientific notation and more than 18 decimals are rejected.
+    pub fn new(amount: &str) -> Result<Self> {
+        Ok(Self { inner: parse_human(amount, EGLD_DECIMALS)? })
+    }
+    /// Create an amount of eGLD from its denominated form, e.g., "1000000000000000000" for 1 eGLD
+    pub fn from_denominated(amount: &str) -> Result<Self> {
+        Ok(Self { inner: parse_digits(amount)? })
+    }
+    /// Convert to human readable form
+    pub fn to_human(&self) -> HumanCurrencyAmount {
+        HumanCurrencyAmount { denominated: self.clone() }
+    }
+    /// Convert to a `TokenAmount`, for use alongside ESDT amounts
+    pub fn to_token_amount(&self) -> TokenAmount {
+        TokenAmount {
+            base: self.inner.clone(),
+            decimals: EGLD_DECIMALS,
+            identifier: "EGLD".to_string()
+        }
+    }
+}
+
+impl std::fmt::Display for ElrondCurrencyAmount {
+    /// Denominated form, as used for serialization. Before 0.2.0, amounts fetched from the
+    /// network displayed in eGLD: use `to_human` for that.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.inner)
+    }
+}
+
+impl FromStr for ElrondCurrencyAmount {
+    type Err = ElrondClientError;
+    /// Parse the denominated form
+    fn from_str(amount: &str) -> Result<Self> {
+        Self::from_denominated(amount)
+    }
+}
+
+impl TryFrom<String> for ElrondCurrencyAmount {
+    type Error = ElrondClientError;
+    fn try_from(amount: String) -> Result<Self> {
+        Self::from_denominated(&amount)
+    }
+}
+
+impl From<ElrondCurrencyAmount> for String {
+    fn from(amount: ElrondCurrencyAmount) -> Self {
+        amount.to_string()
+    }
+}
+
+/// eGLD in human readable form, where "1" is one eGLD
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HumanCurrencyAmount {
+    denominated: ElrondCurrencyAmount
+}
+
+impl HumanCurrencyAmount {
+    /// Parse a human input, e.g., "0.5" for half an eGLD. Negative values, scientific notation
+    /// and more than 18 decimals are rejected.
+    pub fn new(amount: &str) -> Result<Self> {
+        Ok(Self { denominated: ElrondCurrencyAmount::new(amount)? })
+    }
+    /// Convert to denominated form
+    pub fn to_denominated(&self) -> ElrondCurrencyAmount {
+        self.denominated.clone()
+    }
+}
+
+impl std::fmt::Display for HumanCurrencyAmount {
+    /// Human readable form, e.g., "0.5", with the same precision rules as `TokenAmount`
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", format_base_units(&self.denominated.inner, EGLD_DECIMALS, f.precision()))
+    }
+}
+
+impl FromStr for HumanCurrencyAmount {
+    type Err = ElrondClientError;
+    fn from_str(amount: &str) -> Result<Self> {
+        Self::new(amount)
+    }
+}
+
+impl TokenAmount {
+    /// Create an amount from its blockchain representation, an integer number of base units
+    pub fn from_base_units(base_units: &str, decimals: u32, identifier: &str) -> Result<Self> {
+        Ok(Self {
+            base: parse_digits(base_units)?,
+            decimals,
+            identifier: identifier.to_string()
+        })
+    }
+    /// Create an amount from a human readable value, e.g., "1.5". Values with more decimals than
+    /// the token allows are rejected rather than truncated.
+    pub fn from_human(value: &str, decimals: u32, identifier: &str) -> Result<Self> {
+        Ok(Self {
+            base: parse_human(value, decimals)?,
+            decimals,
+            identifier: identifier.to_string()
+        })
+    }
+    /// Zero amount of a token
+    pub fn zero(decimals: u32, identifier: &str) -> Self {
+        Self {
+            base: BigUint::from(0u32),
+            decimals,
+            identifier: identifier.to_string()
+        }
+    }
+    /// Integer number of base units, as used on the blockchain
+    pub fn base_units(&self) -> String {
+        self.base.to_string()
+    }
+    /// Number of decimals of the token
+    pub fn decimals(&self) -> u32 {
+        self.decimals
+    }
+    /// Identifier of the token, e.g., "WEGLD-bd4d79"
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+    /// Whether the amount is zero
+    pub fn is_zero(&self) -> bool {
+        self.base == BigUint::from(0u32)
+    }
+    /// Human readable amount showing exactly `displayed_decimals` decimals, truncating the rest
+    pub fn format(&self, displayed_decimals: usize) -> String {
+        format_base_units(&self.base, self.decimals, Some(displayed_decimals))
+    }
+    /// Add two amounts of the same token
+    pub fn checked_add(&self, other: &TokenAmount) -> Result<TokenAmount> {
+        self.check_same_token(other)?;
+        Ok(self.with_base(&self.base + &other.base))
+    }
+    /// Subtract an amount of the same token, failing if the result would be negative
+    pub fn checked_sub(&self, other: &TokenAmount) -> Result<TokenAmount> {
+        self.check_same_token(other)?;
+        if self.base < other.base {
+            return Err(ElrondClientError::new(
+                &format!("cannot subtract {} from {} {}", other, self, self.identifier)
+            ));
+        }
+        Ok(self.with_base(&self.base - &other.base))
+    }
+    /// Multiply the amount by an integer factor
+    pub fn checked_mul(&self, factor: u128) -> Result<TokenAmount> {
+        Ok(self.with_base(&self.base * BigUint::from(factor)))
+    }
+    /// Divide the amount by an integer divisor, rounding down to a whole number of base units
+    pub fn checked_div(&self, divisor: u128) -> Result<TokenAmount> {
+        if divisor == 0 {
+            return Err(ElrondClientError::new("cannot divide amount by zero"));
+        }
+        Ok(self.with_base(&self.base / BigUint::from(divisor)))
+    }
+    fn with_base(&self, base: BigUint) -> TokenAmount {
+        Self {
+            base,
+            decimals: self.decimals,
+            identifier: self.identifier.clone()
+        }
+    }
+    fn check_same_token(&self, other: &TokenAmount) -> Result<()> {
+        if self.identifier != other.identifier || self.decimals != other.decimals {
+            Err(ElrondClientError::new(
+                &format!("cannot combine amounts of '{}' and '{}'", self.identifier, other.identifier)
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl PartialOrd for TokenAmount {
+    /// Amounts are only ordered when they are of the same token
+    fn partial_cmp(&self, other: &TokenAmount) -> Option<Ordering> {
+        self.check_same_token(other).ok()?;
+        Some(self.base.cmp(&other.base))
+    }
+}
+
+impl std::fmt::Display for TokenAmount {
+    /// Human readable amount with the decimals of the token applied. Trailing zeros are removed,
+    /// unless a precision is given (e.g., `{:.2}`), in which case exactly that many decimals
+    /// are shown.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", format_base_units(&self.base, self.decimals, f.precision()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TokenAmount, ElrondCurrencyAmount, HumanCurrencyAmount};
+    use proptest::prelude::*;
+
+    fn usdc(value: &str) -> TokenAmount {
+        TokenAmount::from_human(value, 6, "USDC-c76f1f").unwrap()
+    }
+
+    #[test]
+    fn display_applies_decimals() {
+        let amount = TokenAmount::from_base_units("1500000", 6, "USDC-c76f1f").unwrap();
+        assert_eq!(amount.to_string(), "1.5");
+        let amount = TokenAmount::from_base_units("42", 6, "USDC-c76f1f").unwrap();
+        assert_eq!(amount.to_string(), "0.000042");
+        let amount = TokenAmount::from_base_units("7", 0, "NFT-123456-01").unwrap();
+        assert_eq!(amount.to_string(), "7");
+        assert_eq!(amount.base_units(), "7");
+    }
+
+    #[test]
+    fn rejects_non_integer_base_units() {
+        assert!(TokenAmount::from_base_units("-1", 6, "USDC-c76f1f").is_err());
+        assert!(TokenAmount::from_base_units("1.5", 6, "USDC-c76f1f").is_err());
+        assert!(TokenAmount::from_base_units("1e6", 6, "USDC-c76f1f").is_err());
+        assert!(TokenAmount::from_base_units("", 6, "USDC-c76f1f").is_err());
+    }
+
+    #[test]
+    fn parse_human_values() {
+        assert_eq!(usdc("1.25").base_units(), "1250000");
+        assert_eq!(usdc("0.000001").base_units(), "1");
+        assert_eq!(usdc("3").base_units(), "3000000");
+        assert_eq!(usdc("1.50000000").base_units(), "1500000");
+        let err = TokenAmount::from_human("0.0000001", 6, "USDC-c76f1f").unwrap_err();
+        assert_eq!(err.to_string(), "'0.0000001' has more than 6 decimals");
+        for invalid in &["-1", "1e3", "+1", " 1", "1.", ".5", "1.2.3", "abc", ""] {
+            assert!(TokenAmount::from_human(invalid, 6, "USDC-c76f1f").is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn format_with_displayed_decimals() {
+        let amount = usdc("1234.56789");
+        assert_eq!(amount.format(2), "1234.56");
+        assert_eq!(amount.format(0), "1234");
+        assert_eq!(amount.format(8), "1234.56789000");
+        assert_eq!(format!("{:.3}", amount), "1234.567");
+        assert_eq!(format!("{}", amount), "1234.56789");
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        assert_eq!(usdc("1.5").checked_add(&usdc("2.25")).unwrap(), usdc("3.75"));
+        assert_eq!(usdc("1.5").checked_sub(&usdc("0.5")).unwrap(), usdc("1"));
+        assert!(usdc("0.5").checked_sub(&usdc("1.5")).is_err());
+        assert_eq!(usdc("1.5").checked_mul(3).unwrap(), usdc("4.5"));
+        assert_eq!(usdc("1").checked_div(3).unwrap(), usdc("0.333333"));
+        assert!(usdc("1").checked_div(0).is_err());
+        let other = TokenAmount::from_human("1", 18, "WEGLD-bd4d79").unwrap();
+        assert!(usdc("1").checked_add(&other).is_err());
+    }
+
+    #[test]
+    fn comparison_within_token() {
+        assert!(usdc("1.5") > usdc("1.25"));
+        assert!(usdc("0") < usdc("0.000001"));
+        let other = TokenAmount::from_human("1", 18, "WEGLD-bd4d79").unwrap();
+        assert_eq!(usdc("1").partial_cmp(&other), None);
+        assert!(TokenAmount::zero(6, "USDC-c76f1f").is_zero());
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let amount = usdc("1.5");
+        let serialized = serde_json::to_string(&amount).unwrap();
+        assert_eq!(serialized, "{\"identifier\":\"USDC-c76f1f\",\"decimals\":6,\"amount\":\"1500000\"}");
+        let deserialized: TokenAmount = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, amount);
+        let invalid = "{\"identifier\":\"USDC-c76f1f\",\"decimals\":6,\"amount\":\"-1\"}";
+        assert!(serde_json::from_str::<TokenAmount>(invalid).is_err());
+    }
+
+    #[test]
+    fn egld_denominated_and_human_forms() {
+        let amount = ElrondCurrencyAmount::new("0.001").unwrap();
+        assert_eq!(amount.to_string(), "1000000000000000");
+        assert_eq!(amount.to_human().to_string(), "0.001");
+        let balance = ElrondCurrencyAmount::from_denominated("100000000000000").unwrap();
+        assert_eq!(balance.to_human().to_string(), "0.0001");
+        assert_eq!(format!("{:.2}", ElrondCurrencyAmount::new("12.3456").unwrap().to_human()), "12.34");
+        assert_eq!(amount.to_token_amount().to_string(), "0.001");
+    }
+
+    #[test]
+    fn egld_rejects_lossy_input() {
+        let err = ElrondCurrencyAmount::new("0.0000000000000000001").unwrap_err();
+        assert_eq!(err.to_string(), "'0.0000000000000000001' has more than 18 decimals");
+        for invalid in &["-1", "1e18", "1E-3", "0x10", "1,5", ""] {
+            assert!(ElrondCurrencyAmount::new(invalid).is_err(), "{}", invalid);
+            assert!(HumanCurrencyAmount::new(invalid).is_err(), "{}", invalid);
+        }
+        for invalid in &["-1", "1.5", "1e18", ""] {
+            assert!(ElrondCurrencyAmount::from_denominated(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn egld_serializes_as_denominated_string() {
+        let amount = ElrondCurrencyAmount::new("1.5").unwrap();
+        assert_eq!(serde_json::to_string(&amount).unwrap(), "\"1500000000000000000\"");
+        let parsed: ElrondCurrencyAmount = serde_json::from_str("\"1500000000000000000\"").unwrap();
+        assert_eq!(parsed, amount);
+        assert!(serde_json::from_str::<ElrondCurrencyAmount>("\"1.5\"").is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn denominated_round_trip(base in any::<u128>()) {
+            let denominated = base.to_string();
+            let amount = ElrondCurrencyAmount::from_denominated(&denominated).unwrap();
+            prop_assert_eq!(amount.to_string(), denominated);
+        }
+
+        #[test]
+        fn human_round_trip(base in any::<u128>()) {
+            let amount = ElrondCurrencyAmount::from_denominated(&base.to_string()).unwrap();
+            let human = amount.to_human().to_string();
+            prop_assert_eq!(HumanCurrencyAmount::new(&human).unwrap().to_denominated(), amount);
+        }
+
+        #[test]
+        fn human_input_round_trip(integer in 0u64.., fraction in "[0-9]{0,18}") {
+            let input = if fraction.is_empty() {
+                integer.to_string()
+            } else {
+                format!("{}.{}", integer, fraction)
+            };
+            let human = HumanCurrencyAmount::new(&input).unwrap();
+            // display only drops trailing zeros
+            let expected = input.trim_end_matches('0').trim_end_matches('.');
+            let expected = if input.contains('.') { expected.to_string() } else { input.clone() };
+            prop_assert_eq!(human.to_string(), expected);
+        }
+
+        #[test]
+        fn over_precise_input_rejected(integer in 0u64.., fraction in "[0-9]{18}[1-9]") {
+            let input = format!("{}.{}", integer, fraction);
+            prop_assert!(ElrondCurrencyAmount::new(&input).is_err());
+        }
+
+        #[test]
+        fn token_amount_round_trip(base in any::<u128>(), decimals in 0u32..30) {
+            let amount = TokenAmount::from_base_units(&base.to_string(), decimals, "TKN-123456").unwrap();
+            let parsed = TokenAmount::from_human(&amount.to_string(), decimals, "TKN-123456").unwrap();
+            prop_assert_eq!(parsed, amount);
+        }
+    }
+}
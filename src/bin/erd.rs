@@ -0,0 +1,308 @@
+//! `erd`, a command-line wallet for the Elrond network built on `elrond_rust`.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+use clap::{Parser, Subcommand, ValueEnum, Args};
+use serde_json::{json, Value};
+use elrond_rust::{Account, Client, ElrondClientError, Network, Result, UnsignedTransaction};
+
+/// Command-line wallet for the Elrond network
+#[derive(Parser)]
+#[command(name = "erd", version)]
+struct Cli {
+    /// Print JSON instead of human-readable output
+    #[arg(long, global = true)]
+    json: bool,
+    /// Gateway to send requests to
+    #[arg(long, global = true, default_value = "https://api.elrond.com")]
+    proxy: String,
+    #[command(subcommand)]
+    command: Command
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a new account and save its secret key
+    New {
+        /// File to write the secret key to, which must not exist yet
+        #[arg(long)]
+        out: String,
+        #[arg(long, value_enum, default_value = "pem")]
+        format: KeyFormat,
+        /// File holding the password of keystores
+        #[arg(long)]
+        password_file: Option<String>
+    },
+    /// Convert a secret key file between formats
+    Convert {
+        #[command(flatten)]
+        key: KeyArgs,
+        /// File to write the converted key to, which must not exist yet
+        #[arg(long)]
+        out: String,
+        /// Format to convert to
+        #[arg(long, value_enum)]
+        to: KeyFormat,
+        /// File holding the password of the converted keystore
+        #[arg(long)]
+        out_password_file: Option<String>
+    },
+    /// Show the address of a secret key
+    Address {
+        #[command(flatten)]
+        key: KeyArgs
+    },
+    /// Build and sign an eGLD transfer, broadcasting it with `--send`
+    Transfer {
+        #[command(flatten)]
+        key: KeyArgs,
+        /// bech32 address of the receiver
+        #[arg(long)]
+        to: String,
+        /// Amount of eGLD, e.g., "0.5"
+        #[arg(long)]
+        value: String,
+        /// Nonce of the sender, looked up on the network if missing
+        #[arg(long)]
+        nonce: Option<u64>,
+        /// Data attached to the transfer
+        #[arg(long)]
+        data: Option<String>,
+        #[arg(long)]
+        gas_limit: Option<u64>,
+        #[arg(long, default_value = "1")]
+        chain: String,
+        /// Broadcast the signed transaction
+        #[arg(long)]
+        send: bool
+    },
+    /// Get the nonce of an address
+    Nonce {
+        address: String
+    },
+    /// Get the eGLD balance of an address
+    Balance {
+        address: String
+    },
+    /// Wait until a transaction is executed
+    Wait {
+        tx_hash: String,
+        /// Seconds to wait for before giving up
+        #[arg(long, default_value = "60")]
+        timeout: u64
+    }
+}
+
+/// Format of secret key files
+#[derive(Clone, Copy, ValueEnum)]
+enum KeyFormat {
+    /// Hex encoded secret key
+    Hex,
+    /// PEM file, as used by command-line tools
+    Pem,
+    /// Password-encrypted JSON keystore, as used by wallets
+    Keystore
+}
+
+/// Location and format of the secret key to use
+#[derive(Args)]
+struct KeyArgs {
+    /// File holding the secret key
+    #[arg(long)]
+    key: String,
+    #[arg(long, value_enum, default_value = "pem")]
+    format: KeyFormat,
+    /// File holding the password of keystores
+    #[arg(long)]
+    password_file: Option<String>
+}
+
+/// Result of a command, printed as text or JSON
+struct Output {
+    human: String,
+    json: Value
+}
+
+fn error(message: &str) -> ElrondClientError {
+    ElrondClientError::new(message)
+}
+
+fn read_file(path: &str) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| error(&format!("could not read '{}': {}", path, e)))
+}
+
+/// Write a secret key to a new file, readable by its owner only
+fn write_key_file(path: &str, contents: &str) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|e| error(&format!("could not write '{}': {}", path, e)))
+}
+
+fn read_password(password_file: &Option<String>) -> Result<String> {
+    let path = password_file
+        .as_ref()
+        .ok_or_else(|| error("keystores need a --password-file"))?;
+    Ok(read_file(path)?.trim_end_matches(&['\r', '\n'][..]).to_string())
+}
+
+/// Decode a secret key file
+fn load_account(contents: &str, format: KeyFormat, password_file: &Option<String>) -> Result<Account> {
+    match format {
+        KeyFormat::Hex => Account::from_string(contents.trim()),
+        KeyFormat::Pem => Account::from_pem(contents),
+        KeyFormat::Keystore => Account::from_keystore(contents, &read_password(password_file)?)
+    }
+}
+
+/// Encode the secret key of an account
+fn save_account(account: &Account, format: KeyFormat, password_file: &Option<String>) -> Result<String> {
+    match format {
+        KeyFormat::Hex => Ok(format!("{}\n", account.export_secret_hex().as_str())),
+        KeyFormat::Pem => Ok(account.to_pem().to_string()),
+        KeyFormat::Keystore => account.to_keystore(&read_password(password_file)?)
+    }
+}
+
+fn key_account(key: &KeyArgs) -> Result<Account> {
+    load_account(&read_file(&key.key)?, key.format, &key.password_file)
+}
+
+fn address_output(account: &Account) -> Output {
+    let address = account.address.to_string();
+    Output { human: address.clone(), json: json!({ "address": address }) }
+}
+
+fn run(cli: Cli) -> Result<Output> {
+    let client = Client::with_transport(&cli.proxy, elrond_rust::UreqTransport::new());
+    match cli.command {
+        Command::New { out, format, password_file } => {
+            let account = Account::generate()?;
+            write_key_file(&out, &save_account(&account, format, &password_file)?)?;
+            Ok(address_output(&account))
+        }
+        Command::Convert { key, out, to, out_password_file } => {
+            let account = key_account(&key)?;
+            write_key_file(&out, &save_account(&account, to, &out_password_file)?)?;
+            Ok(address_output(&account))
+        }
+        Command::Address { key } => Ok(address_output(&key_account(&key)?)),
+        Command::Transfer { key, to, value, nonce, data, gas_limit, chain, send } => {
+            let account = key_account(&key)?;
+            let sender = account.address.to_string();
+            let nonce = match nonce {
+                Some(nonce) => nonce,
+                None => client.get_address_nonce(&sender)?
+            };
+            let mut tx = UnsignedTransaction::new(nonce, &value, &to, &sender, Network::Custom(chain))?;
+            if let Some(data) = data {
+                tx = tx.with_data(data.as_bytes());
+            }
+            if let Some(gas_limit) = gas_limit {
+                tx = tx.with_gas_limit(gas_limit);
+            }
+            let summary = tx.summary()?;
+            let signed = tx.sign(&account)?;
+            let signed_json: Value = serde_json::from_str(&signed.serialize()?).map_err(|_| {
+                error("could not serialize signed transaction")
+            })?;
+            if send {
+                let tx_hash = client.post_signed_transaction(signed)?;
+                Ok(Output {
+                    human: format!("{}\n  hash:     {}", summary, tx_hash),
+                    json: json!({ "transaction": signed_json, "txHash": tx_hash })
+                })
+            } else {
+                Ok(Output {
+                    human: format!("{}\n\n{}", summary, signed_json),
+                    json: json!({ "transaction": signed_json })
+                })
+            }
+        }
+        Command::Nonce { address } => {
+            let nonce = client.get_address_nonce(&address)?;
+            Ok(Output { human: nonce.to_string(), json: json!({ "address": address, "nonce": nonce }) })
+        }
+        Command::Balance { address } => {
+            let balance = client.get_address_balance(&address)?;
+            Ok(Output {
+                human: format!("{} eGLD", balance.to_human()),
+                json: json!({ "address": address, "balance": balance.to_string() })
+            })
+        }
+        Command::Wait { tx_hash, timeout } => {
+            let deadline = Instant::now() + Duration::from_secs(timeout);
+            loop {
+                let status = client.get_transaction_status(&tx_hash)?;
+                if !matches!(status.as_str(), "pending" | "received" | "partially-executed") {
+                    return Ok(Output {
+                        human: status.clone(),
+                        json: json!({ "txHash": tx_hash, "status": status })
+                    });
+                }
+                if Instant::now() >= deadline {
+                    return Err(error(&format!("transaction still '{}' after {} seconds", status, timeout)));
+                }
+                thread::sleep(Duration::from_secs(2));
+            }
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let json_output = cli.json;
+    match run(cli) {
+        Ok(output) if json_output => println!("{}", output.json),
+        Ok(output) => println!("{}", output.human),
+        Err(e) => {
+            if json_output {
+                println!("{}", json!({ "error": e.to_string() }));
+            } else {
+                eprintln!("error: {}", e);
+            }
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_account, save_account, write_key_file, KeyFormat};
+    use elrond_rust::Account;
+
+    #[test]
+    fn key_formats_round_trip() {
+        let account = Account::generate().unwrap();
+        for &format in &[KeyFormat::Hex, KeyFormat::Pem] {
+            let saved = save_account(&account, format, &None).unwrap();
+            let loaded = load_account(&saved, format, &None).unwrap();
+            assert_eq!(loaded.address, account.address);
+        }
+        assert!(save_account(&account, KeyFormat::Keystore, &None).is_err());
+    }
+
+    #[test]
+    fn key_files_are_private_and_never_overwritten() {
+        let path = std::env::temp_dir().join(format!("erd-key-{}.pem", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        write_key_file(path, "secret").unwrap();
+        assert!(write_key_file(path, "other secret").is_err());
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "secret");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,188 @@
+//! Blocks produced by the shards, and the hyperblocks of the metachain notarizing them. The
+//! transactions of a hyperblock are those of every shard block it notarizes, so following
+//! finalized hyperblocks follows the whole network.
+
+use serde::{Deserialize, Deserializer};
+use super::{ElrondAddress, ElrondCurrencyAmount};
+
+/// Shard id of the metachain, whose hyperblocks notarize the blocks of every shard
+pub const METACHAIN_SHARD: u32 = 4294967295;
+
+/// Progress of a shard (or of the metachain), as seen by the node answering
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct NetworkStatus {
+    #[serde(rename = "erd_current_round")]
+    pub current_round: u64,
+    #[serde(rename = "erd_epoch_number")]
+    pub epoch: u32,
+    /// Nonce of the latest block
+    #[serde(rename = "erd_nonce")]
+    pub nonce: u64,
+    /// Nonce of the latest final block, which can no longer be reverted
+    #[serde(rename = "erd_highest_final_nonce")]
+    pub highest_final_nonce: u64
+}
+
+/// Decode the base64 data field of a transaction, sent as null or omitted when empty
+fn base64_data<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let data = Option::<String>::deserialize(deserializer)?.unwrap_or_default();
+    base64::decode(&data).map_err(serde::de::Error::custom)
+}
+
+/// Transaction included in a block. Besides transactions sent by accounts ("normal"), blocks hold
+/// smart contract results ("unsigned"), rewards ("reward") and failed transactions ("invalid").
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTransaction {
+    pub hash: String,
+    /// "normal", "unsigned", "reward" or "invalid"
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub nonce: u64,
+    pub value: ElrondCurrencyAmount,
+    pub sender: ElrondAddress,
+    pub receiver: ElrondAddress,
+    #[serde(default)]
+    pub gas_price: u64,
+    #[serde(default)]
+    pub gas_limit: u64,
+    #[serde(default, deserialize_with = "base64_data")]
+    pub data: Vec<u8>,
+    #[serde(default)]
+    pub source_shard: u32,
+    #[serde(default)]
+    pub destination_shard: u32,
+    /// e.g., "success", "fail" or "pending"
+    #[serde(default)]
+    pub status: String,
+    /// Hash of the transaction a smart contract result originates from
+    #[serde(default)]
+    pub original_transaction_hash: Option<String>
+}
+
+/// Group of transactions between two shards within a block
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MiniBlock {
+    #[serde(default)]
+    transactions: Vec<BlockTransaction>
+}
+
+/// Block of a shard (or of the metachain)
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(from = "BlockRepr")]
+pub struct Block {
+    pub hash: String,
+    pub nonce: u64,
+    pub round: u64,
+    pub epoch: u32,
+    pub shard: u32,
+    /// Timestamp in seconds
+    pub timestamp: u64,
+    pub prev_block_hash: String,
+    /// Transactions of every miniblock of the block
+    pub transactions: Vec<BlockTransaction>
+}
+
+/// Block as sent by the API, with transactions grouped in miniblocks
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BlockRepr {
+    hash: String,
+    nonce: u64,
+    round: u64,
+    epoch: u32,
+    shard: u32,
+    #[serde(default)]
+    timestamp: u64,
+    #[serde(default)]
+    prev_block_hash: String,
+    #[serde(default)]
+    mini_blocks: Vec<MiniBlock>
+}
+
+impl From<BlockRepr> for Block {
+    fn from(repr: BlockRepr) -> Self {
+        Self {
+            hash: repr.hash,
+            nonce: repr.nonce,
+            round: repr.round,
+            epoch: repr.epoch,
+            shard: repr.shard,
+            timestamp: repr.timestamp,
+            prev_block_hash: repr.prev_block_hash,
+            transactions: repr.mini_blocks.into_iter().flat_map(|mini_block| mini_block.transactions).collect()
+        }
+    }
+}
+
+/// Shard block notarized by a hyperblock
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShardBlockInfo {
+    pub hash: String,
+    pub nonce: u64,
+    pub shard: u32
+}
+
+/// Metachain block, along with the transactions of the shard blocks it notarizes
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hyperblock {
+    pub hash: String,
+    pub nonce: u64,
+    pub round: u64,
+    pub epoch: u32,
+    /// Timestamp in seconds
+    pub timestamp: u64,
+    #[serde(default)]
+    pub prev_block_hash: String,
+    #[serde(default)]
+    pub shard_blocks: Vec<ShardBlockInfo>,
+    #[serde(default)]
+    pub transactions: Vec<BlockTransaction>
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::{Block, Hyperblock};
+
+    const ALICE: &str = "erd1qyu5wthldzr8wx5c9ucg8kjagg0jfs53s8nr3zpz3hypefsdd8ssycr6th";
+    const BOB: &str = "erd1spyavw0956vq68xj8y4tenjpq2wd5a9p2c6j8gsz7ztyrnpxrruqzu66jx";
+
+    #[test]
+    fn parse_block_with_miniblocks() {
+        let block: Block = serde_json::from_value(json!({
+            "hash": "bb", "nonce": 10, "round": 11, "epoch": 1, "shard": 0, "timestamp": 1650000000,
+            "prevBlockHash": "aa",
+            "miniBlocks": [
+                {"hash": "m1", "transactions": [
+                    {"type": "normal", "hash": "t1", "nonce": 5, "value": "1000", "sender": ALICE, "receiver": BOB, "data": "aGVsbG8="}
+                ]},
+                {"hash": "m2"}
+            ]
+        })).unwrap();
+        assert_eq!(block.prev_block_hash, "aa");
+        assert_eq!(block.transactions.len(), 1);
+        assert_eq!(block.transactions[0].data, b"hello");
+        assert_eq!(block.transactions[0].receiver.to_string(), BOB);
+    }
+
+    #[test]
+    fn parse_hyperblock() {
+        let hyperblock: Hyperblock = serde_json::from_value(json!({
+            "hash": "cc", "nonce": 20, "round": 21, "epoch": 1, "timestamp": 1650000006,
+            "shardBlocks": [{"hash": "bb", "nonce": 10, "round": 11, "shard": 0}],
+            "transactions": [
+                {"type": "unsigned", "hash": "t2", "nonce": 0, "value": "0", "sender": BOB, "receiver": ALICE,
+                 "data": null, "originalTransactionHash": "t1", "status": "success"}
+            ]
+        })).unwrap();
+        assert_eq!(hyperblock.shard_blocks[0].shard, 0);
+        let transaction = &hyperblock.transactions[0];
+        assert_eq!(transaction.kind, "unsigned");
+        assert!(transaction.data.is_empty());
+        assert_eq!(transaction.original_transaction_hash.as_deref(), Some("t1"));
+    }
+}
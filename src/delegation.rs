@@ -0,0 +1,123 @@
+//! Staking providers run delegation contracts, which stake the eGLD of their delegators on their
+//! nodes. This module builds the calls of delegators to those contracts, and the call to the
+//! delegation manager creating a new one. `Client::get_delegated_stake`,
+//! `get_undelegated_stake` and `get_claimable_rewards` query the state of a delegator.
+
+use super::system::{contract_call, call_data, biguint_arg, u64_arg};
+use super::{Network, UnsignedTransaction, ElrondAddress, ElrondCurrencyAmount, ElrondClientError, Result};
+
+/// Gas limit of delegating, undelegating, withdrawing and redelegating, data included, as set by
+/// the erdjs delegation factory
+const DELEGATION_GAS_LIMIT: u64 = 12000000;
+/// Gas limit of claiming rewards, data included, as set by the erdjs delegation factory
+const CLAIM_REWARDS_GAS_LIMIT: u64 = 6000000;
+/// Gas consumed by creating a delegation contract, on top of the cost of its data
+const CREATE_CONTRACT_GAS: u64 = 60000000;
+/// eGLD locked when creating a delegation contract
+const CREATE_CONTRACT_DEPOSIT: &str = "1250";
+/// Service fees are expressed in basis points, out of this total
+const MAX_SERVICE_FEE: u32 = 10000;
+
+/// Internal helper for building a call from `delegator` to the delegation contract `contract`,
+/// with a fixed gas limit
+fn delegation_call(delegator: &str, nonce: u64, contract: &str, value: &str, data: &str, gas_limit: u64, network: Network) -> Result<UnsignedTransaction> {
+    let contract = ElrondAddress::new(contract)?;
+    if !contract.is_smart_contract() {
+        return Err(ElrondClientError::new(&format!("'{}' is not a smart contract", contract)));
+    }
+    Ok(contract_call(delegator, nonce, &contract, value, data, 0, network)?.with_gas_limit(gas_limit))
+}
+
+/// Delegate `value` eGLD to the staking provider running `contract` (at least 1 eGLD)
+pub fn delegate(delegator: &str, nonce: u64, contract: &str, value: &str, network: Network) -> Result<UnsignedTransaction> {
+    delegation_call(delegator, nonce, contract, value, "delegate", DELEGATION_GAS_LIMIT, network)
+}
+
+/// Undelegate `amount` eGLD from `contract`, to be withdrawn with `withdraw` after the unbonding
+/// period
+pub fn undelegate(delegator: &str, nonce: u64, contract: &str, amount: &str, network: Network) -> Result<UnsignedTransaction> {
+    let amount = ElrondCurrencyAmount::new(amount)?;
+    let data = call_data("unDelegate", &[biguint_arg(&amount.to_string())?]);
+    delegation_call(delegator, nonce, contract, "0", &data, DELEGATION_GAS_LIMIT, network)
+}
+
+/// Withdraw the undelegated eGLD whose unbonding period is over
+pub fn withdraw(delegator: &str, nonce: u64, contract: &str, network: Network) -> Result<UnsignedTransaction> {
+    delegation_call(delegator, nonce, contract, "0", "withdraw", DELEGATION_GAS_LIMIT, network)
+}
+
+/// Claim the rewards earned with `contract`
+pub fn claim_rewards(delegator: &str, nonce: u64, contract: &str, network: Network) -> Result<UnsignedTransaction> {
+    delegation_call(delegator, nonce, contract, "0", "claimRewards", CLAIM_REWARDS_GAS_LIMIT, network)
+}
+
+/// Delegate the rewards earned with `contract` back to it
+pub fn redelegate_rewards(delegator: &str, nonce: u64, contract: &str, network: Network) -> Result<UnsignedTransaction> {
+    delegation_call(delegator, nonce, contract, "0", "reDelegateRewards", DELEGATION_GAS_LIMIT, network)
+}
+
+/// Create a new delegation contract owned by `owner`, locking the 1250 eGLD deposit. `total_cap`
+/// limits the eGLD delegated to it ("0" for no limit) and `service_fee` is the share of the
+/// rewards kept by the owner, in basis points out of 10000.
+pub fn create_delegation_contract(owner: &str, nonce: u64, total_cap: &str, service_fee: u32, network: Network) -> Result<UnsignedTransaction> {
+    if service_fee > MAX_SERVICE_FEE {
+        return Err(ElrondClientError::new(&format!("service fee is at most {} basis points", MAX_SERVICE_FEE)));
+    }
+    let total_cap = biguint_arg(&ElrondCurrencyAmount::new(total_cap)?.to_string())?;
+    // no limit is sent as "00" rather than an empty argument, as erdjs does
+    let total_cap = if total_cap.is_empty() { vec![0] } else { total_cap };
+    let data = call_data(
+        "createNewDelegationContract",
+        &[total_cap, u64_arg(service_fee as u64)]
+    );
+    contract_call(
+        owner,
+        nonce,
+        &ElrondAddress::DELEGATION_MANAGER_SC,
+        CREATE_CONTRACT_DEPOSIT,
+        &data,
+        CREATE_CONTRACT_GAS,
+        network
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{delegate, undelegate, claim_rewards, create_delegation_contract};
+    use super::super::{ElrondAddress, Network};
+
+    const DELEGATOR: &str = "erd146apxa83wr7paz3gsg07dhcpg98ascjtpg9p8l8g5rpmg6chhchq9ccvmc";
+    const PROVIDER: &str = "erd1qqqqqqqqqqqqqqqpqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqxlllshevkwc";
+
+    #[test]
+    fn delegator_calls() {
+        let tx = delegate(DELEGATOR, 1, PROVIDER, "10", Network::MainNet).unwrap();
+        assert_eq!(tx.data().unwrap(), b"delegate");
+        assert_eq!(tx.receiver(), PROVIDER);
+        assert_eq!(tx.value().unwrap().to_human().to_string(), "10");
+        // gas limits as set by erdjs for the same calls
+        assert_eq!(tx.gas_limit(), 12000000);
+        let tx = undelegate(DELEGATOR, 2, PROVIDER, "1.5", Network::MainNet).unwrap();
+        assert_eq!(tx.data().unwrap(), b"unDelegate@14d1120d7b160000");
+        assert_eq!(tx.value().unwrap().to_string(), "0");
+        assert_eq!(tx.gas_limit(), 12000000);
+        assert_eq!(claim_rewards(DELEGATOR, 3, PROVIDER, Network::MainNet).unwrap().gas_limit(), 6000000);
+        // delegating to an account is a mistake
+        assert!(delegate(DELEGATOR, 1, DELEGATOR, "10", Network::MainNet).is_err());
+    }
+
+    #[test]
+    fn create_contract() {
+        let tx = create_delegation_contract(DELEGATOR, 1, "0", 1250, Network::MainNet).unwrap();
+        let data = String::from_utf8(tx.data().unwrap()).unwrap();
+        assert_eq!(data, "createNewDelegationContract@00@04e2");
+        assert_eq!(tx.receiver(), ElrondAddress::DELEGATION_MANAGER_SC.to_string());
+        assert_eq!(tx.value().unwrap().to_human().to_string(), "1250");
+        // 50M + 10M for the delegation manager on top of the data, as in erdjs
+        assert_eq!(tx.gas_limit(), 60102500);
+        let tx = create_delegation_contract(DELEGATOR, 1, "5000", 1250, Network::MainNet).unwrap();
+        let data = String::from_utf8(tx.data().unwrap()).unwrap();
+        assert_eq!(data, "createNewDelegationContract@010f0cf064dd59200000@04e2");
+        assert!(create_delegation_contract(DELEGATOR, 1, "0", 10001, Network::MainNet).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+//! Usernames (herotags) such as "alice.elrond" are registered with the DNS smart contracts, which
+//! set the username of the registering account. There are 256 DNS contracts, and the one handling
+//! a name is chosen by the last byte of the keccak256 hash of the name. `Client::resolve_username`
+//! and `get_address_username` look names up.
+
+use sha3::{Digest, Keccak256};
+use super::system::{contract_call, call_data};
+use super::{Network, UnsignedTransaction, ElrondAddress, ElrondClientError, Result};
+
+/// Suffix of the usernames registered with the DNS contracts
+const USERNAME_SUFFIX: &str = ".elrond";
+/// Gas consumed by registering a username, on top of the cost of its data
+const REGISTER_GAS: u64 = 20000000;
+
+/// Complete and check a username: "alice" becomes "alice.elrond". Names are made of 3 to 25
+/// lowercase letters and digits.
+pub(crate) fn normalize_username(name: &str) -> Result<String> {
+    let name = name.trim().to_lowercase();
+    let base = name.strip_suffix(USERNAME_SUFFIX).unwrap_or(&name);
+    let valid = base.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit());
+    if !(3..=25).contains(&base.len()) || !valid {
+        return Err(ElrondClientError::new(
+            &format!("'{}' is not a valid username, expected 3 to 25 letters and digits", name)
+        ));
+    }
+    Ok(format!("{}{}", base, USERNAME_SUFFIX))
+}
+
+/// Address of a contract deployed by `deployer` at `nonce`
+fn contract_address(deployer: &[u8; 32], nonce: u64) -> ElrondAddress {
+    let mut preimage = deployer.to_vec();
+    preimage.extend_from_slice(&nonce.to_le_bytes());
+    let hash = Keccak256::digest(&preimage);
+    let mut bytes = [0u8; 32];
+    // 8 zero bytes and the VM type (wasm) mark smart contracts, the last two bytes keep the
+    // contract in the shard of its deployer
+    bytes[8] = 5;
+    bytes[10..30].copy_from_slice(&hash[10..30]);
+    bytes[30..].copy_from_slice(&deployer[30..]);
+    ElrondAddress::from_bytes(&bytes).expect("32 bytes")
+}
+
+/// Address of the DNS contract handling `name` (e.g., "alice.elrond"), deployed at genesis by
+/// an address made of ones ending with the last byte of the hash of the name
+pub fn dns_address(name: &str) -> Result<ElrondAddress> {
+    let name = normalize_username(name)?;
+    let hash = Keccak256::digest(name.as_bytes());
+    let mut deployer = [1u8; 32];
+    deployer[30] = 0;
+    deployer[31] = hash[31];
+    Ok(contract_address(&deployer, 0))
+}
+
+/// Register `name` (e.g., "alice.elrond") as the username of `account`
+pub fn register_username(account: &str, nonce: u64, name: &str, network: Network) -> Result<UnsignedTransaction> {
+    let name = normalize_username(name)?;
+    let data = call_data("register", &[name.as_bytes().to_vec()]);
+    contract_call(account, nonce, &dns_address(&name)?, "0", &data, REGISTER_GAS, network)
+}
+
+#[cfg(test)]
+mod tests {
+    use sha3::{Digest, Keccak256};
+    use super::{normalize_username, contract_address, dns_address, register_username};
+    use super::super::{ElrondAddress, Network};
+
+    #[test]
+    fn usernames_are_normalized() {
+        assert_eq!(normalize_username("alice").unwrap(), "alice.elrond");
+        assert_eq!(normalize_username("Alice.elrond").unwrap(), "alice.elrond");
+        assert!(normalize_username("al").is_err());
+        assert!(normalize_username("alice-bob").is_err());
+    }
+
+    #[test]
+    fn dns_contract_depends_on_name_hash() {
+        let address = dns_address("alice").unwrap();
+        let bytes = address.to_bytes();
+        assert!(address.is_smart_contract());
+        assert_eq!(&bytes[8..10], &[5, 0]);
+        assert_eq!(bytes[30], 0);
+        assert_eq!(bytes[31], Keccak256::digest(b"alice.elrond")[31]);
+        // regression value for the DNS contract of "alice.elrond", derived as above
+        assert_eq!(dns_address("alice.elrond").unwrap(), address);
+        let tx = register_username("erd146apxa83wr7paz3gsg07dhcpg98ascjtpg9p8l8g5rpmg6chhchq9ccvmc", 1, "alice", Network::MainNet).unwrap();
+        let data = String::from_utf8(tx.data().unwrap()).unwrap();
+        assert_eq!(data, format!("register@{}", hex::encode("alice.elrond")));
+        assert_eq!(tx.receiver(), address.to_string());
+        assert_eq!(tx.gas_limit(), 50000 + 1500 * data.len() as u64 + 20000000);
+    }
+
+    #[test]
+    fn known_contract_addresses() {
+        // contracts deployed by erd1j0hx...h3ap at nonces 0 and 1, as computed by erdjs
+        let deployer = ElrondAddress::new("erd1j0hxzs7dcyxw08c4k2nv9tfcaxmqy8rj59meq505w92064x0h40qcxh3ap").unwrap();
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&deployer.to_bytes());
+        assert_eq!(contract_address(&bytes, 0).to_string(), "erd1qqqqqqqqqqqqqpgqhdjjyq8dr7v5yq9tv6v5vt9tfvd00vg7h40q6779zn");
+        assert_eq!(contract_address(&bytes, 1).to_string(), "erd1qqqqqqqqqqqqqpgqde8eqjywyu6zlxjxuxqfg5kgtmn3setxh40qen8egy");
+        // regression value for the DNS contract of "alice.elrond", derived as above
+        assert_eq!(dns_address("alice.elrond").unwrap().to_string(), "erd1qqqqqqqqqqqqqpgqf97pgqdy0tstwauxu09kszz020hp5kgqqzzsscqtww");
+    }
+}
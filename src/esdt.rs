@@ -0,0 +1,615 @@
+//! ESDT tokens (Elrond Standard Digital Tokens): fungible tokens, NFTs and SFTs managed natively
+//! by the protocol, through the ESDT system smart contract. This module builds the transactions
+//! issuing and managing tokens, and creating and updating NFTs.
+
+use super::system::{contract_call, call_data, u64_arg, biguint_arg};
+use super::{Network, UnsignedTransaction, ElrondAddress, TokenAmount, Result, ElrondClientError};
+
+/// Properties of an ESDT token, as recorded by the ESDT system smart contract
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenProperties {
+    pub identifier: String,
+    pub name: String,
+    /// e.g., "FungibleESDT", "NonFungibleESDT", "SemiFungibleESDT" or "MetaESDT"
+    pub token_type: String,
+    pub owner: ElrondAddress,
+    pub supply: TokenAmount,
+    pub burnt: TokenAmount,
+    pub decimals: u32,
+    pub is_paused: bool,
+    pub can_upgrade: bool,
+    pub can_mint: bool,
+    pub can_burn: bool,
+    pub can_change_owner: bool,
+    pub can_pause: bool,
+    pub can_freeze: bool,
+    pub can_wipe: bool,
+    pub can_add_special_roles: bool
+}
+
+/// Special roles held by an address for some token, e.g., "ESDTRoleLocalMint"
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenRoles {
+    /// Address holding the roles
+    pub address: ElrondAddress,
+    pub roles: Vec<String>
+}
+
+/// Details of a single NFT, SFT or MetaESDT held by an address
+#[derive(Clone, Debug, PartialEq)]
+pub struct NftDetails {
+    /// Full identifier including the nonce, e.g., "NFT-123456-0a"
+    pub identifier: String,
+    /// Identifier of the collection, e.g., "NFT-123456"
+    pub collection: String,
+    pub nonce: u64,
+    pub name: String,
+    pub creator: ElrondAddress,
+    /// Royalties in basis points, out of 10000
+    pub royalties: u32,
+    pub hash: Vec<u8>,
+    pub attributes: Vec<u8>,
+    pub uris: Vec<String>,
+    /// Quantity held by the address
+    pub balance: TokenAmount
+}
+
+/// Identifier of the collection an ESDT identifier belongs to, dropping the nonce of NFTs and SFTs
+/// ("NFT-123456-0a" becomes "NFT-123456")
+pub(crate) fn collection_of(identifier: &str) -> String {
+    identifier.splitn(3, '-').take(2).collect::<Vec<&str>>().join("-")
+}
+
+/// Identifier of the NFT or SFT with the given nonce in a collection, the nonce being encoded as
+/// hex with an even number of digits
+pub(crate) fn nft_identifier(collection: &str, nonce: u64) -> String {
+    format!("{}-{}", collection, hex::encode(u64_arg(nonce)))
+}
+
+/// Decode the data returned by `getTokenProperties` on the ESDT system smart contract. The first
+/// fields are positional, the rest are "Name-value" pairs.
+pub(crate) fn parse_token_properties(identifier: &str, return_data: &[Vec<u8>]) -> Result<TokenProperties> {
+    if return_data.len() < 5 {
+        return Err(ElrondClientError::new("token properties response is too short"));
+    }
+    let text = |index: usize| String::from_utf8_lossy(&return_data[index]).to_string();
+    let flags: Vec<(String, String)> = return_data[5..]
+        .iter()
+        .filter_map(|field| {
+            let field = String::from_utf8_lossy(field).to_string();
+            let mut parts = field.splitn(2, '-');
+            Some((parts.next()?.to_string(), parts.next()?.to_string()))
+        })
+        .collect();
+    let flag = |name: &str| flags.iter().any(|(key, value)| key == name && value == "true");
+    let decimals = flags
+        .iter()
+        .find(|(key, _)| key == "NumDecimals")
+        .map(|(_, value)| value.parse::<u32>())
+        .transpose()
+        .map_err(|_| ElrondClientError::new("'NumDecimals' is not a number"))?
+        .unwrap_or(0);
+    Ok(TokenProperties {
+        identifier: identifier.to_string(),
+        name: text(0),
+        token_type: text(1),
+        owner: ElrondAddress::from_bytes(&return_data[2])?,
+        supply: TokenAmount::from_base_units(&text(3), decimals, identifier)?,
+        burnt: TokenAmount::from_base_units(&text(4), decimals, identifier)?,
+        decimals,
+        is_paused: flag("IsPaused"),
+        can_upgrade: flag("CanUpgrade"),
+        can_mint: flag("CanMint"),
+        can_burn: flag("CanBurn"),
+        can_change_owner: flag("CanChangeOwner"),
+        can_pause: flag("CanPause"),
+        can_freeze: flag("CanFreeze"),
+        can_wipe: flag("CanWipe"),
+        can_add_special_roles: flag("CanAddSpecialRoles")
+    })
+}
+
+/// Decode the data returned by `getSpecialRoles`, one "address:role1,role2" entry per holder
+pub(crate) fn parse_token_roles(return_data: &[Vec<u8>]) -> Result<Vec<TokenRoles>> {
+    return_data
+        .iter()
+        .map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            let mut parts = entry.splitn(2, ':');
+            let address = ElrondAddress::new(parts.next().unwrap_or(""))?;
+            let roles = parts
+                .next()
+                .ok_or_else(|| ElrondClientError::new("special roles entry has no ':' separator"))?
+                .split(',')
+                .filter(|role| !role.is_empty())
+                .map(|role| role.to_string())
+                .collect();
+            Ok(TokenRoles { address, roles })
+        })
+        .collect()
+}
+
+/// Fee paid to the ESDT system smart contract for issuing a token
+const ISSUE_FEE: &str = "0.05";
+/// Gas consumed by calls to the ESDT system smart contract, on top of the cost of their data
+const ESDT_SC_CALL_GAS: u64 = 60000000;
+/// Gas consumed by local mints and burns, on top of the cost of their data
+const LOCAL_MINT_BURN_GAS: u64 = 300000;
+/// Highest number of decimals of a token
+const MAX_DECIMALS: u32 = 18;
+
+/// Check that a token name has 3 to 20 alphanumeric characters
+fn check_name(name: &str) -> Result<()> {
+    if !(3..=20).contains(&name.len()) || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(ElrondClientError::new(
+            &format!("token name '{}' must have 3 to 20 alphanumeric characters", name)
+        ));
+    }
+    Ok(())
+}
+
+/// Check that a ticker has 3 to 10 uppercase alphanumeric characters
+fn check_ticker(ticker: &str) -> Result<()> {
+    let valid = ticker.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+    if !(3..=10).contains(&ticker.len()) || !valid {
+        return Err(ElrondClientError::new(
+            &format!("ticker '{}' must have 3 to 10 uppercase alphanumeric characters", ticker)
+        ));
+    }
+    Ok(())
+}
+
+/// Check that a token identifier is a ticker followed by '-' and 6 hex characters, e.g.,
+/// "WEGLD-bd4d79"
+pub(crate) fn check_identifier(identifier: &str) -> Result<()> {
+    let mut parts = identifier.splitn(2, '-');
+    let ticker = parts.next().unwrap_or("");
+    let random = parts.next().unwrap_or("");
+    let valid_random = random.len() == 6
+        && random.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+    if check_ticker(ticker).is_err() || !valid_random {
+        return Err(ElrondClientError::new(&format!("'{}' is not a valid token identifier", identifier)));
+    }
+    Ok(())
+}
+
+/// Properties chosen when issuing a token, all false by default. Some only apply to fungible
+/// tokens (`can_mint`, `can_burn`), others only to NFTs, SFTs and MetaESDTs
+/// (`can_transfer_nft_create_role`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TokenFlags {
+    pub can_freeze: bool,
+    pub can_wipe: bool,
+    pub can_pause: bool,
+    pub can_mint: bool,
+    pub can_burn: bool,
+    pub can_transfer_nft_create_role: bool,
+    pub can_change_owner: bool,
+    pub can_upgrade: bool,
+    pub can_add_special_roles: bool
+}
+
+impl TokenFlags {
+    /// Arguments of the issue call, as "property@true/false" pairs
+    fn args(&self, fungible: bool) -> Vec<Vec<u8>> {
+        let mut flags = vec![("canFreeze", self.can_freeze), ("canWipe", self.can_wipe), ("canPause", self.can_pause)];
+        if fungible {
+            flags.push(("canMint", self.can_mint));
+            flags.push(("canBurn", self.can_burn));
+        } else {
+            flags.push(("canTransferNFTCreateRole", self.can_transfer_nft_create_role));
+        }
+        flags.push(("canChangeOwner", self.can_change_owner));
+        flags.push(("canUpgrade", self.can_upgrade));
+        flags.push(("canAddSpecialRoles", self.can_add_special_roles));
+        flags
+            .into_iter()
+            .flat_map(|(name, value)| vec![name.as_bytes().to_vec(), value.to_string().into_bytes()])
+            .collect()
+    }
+}
+
+/// Special role an address can be given for a token
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenRole {
+    LocalMint,
+    LocalBurn,
+    NftCreate,
+    NftBurn,
+    NftAddQuantity,
+    NftUpdateAttributes,
+    NftAddUri,
+    /// Restricts transfers of the token to holders of this role
+    Transfer
+}
+
+impl TokenRole {
+    /// Name of the role, as used by the protocol, e.g., "ESDTRoleLocalMint"
+    pub fn name(&self) -> &'static str {
+        match self {
+            TokenRole::LocalMint => "ESDTRoleLocalMint",
+            TokenRole::LocalBurn => "ESDTRoleLocalBurn",
+            TokenRole::NftCreate => "ESDTRoleNFTCreate",
+            TokenRole::NftBurn => "ESDTRoleNFTBurn",
+            TokenRole::NftAddQuantity => "ESDTRoleNFTAddQuantity",
+            TokenRole::NftUpdateAttributes => "ESDTRoleNFTUpdateAttributes",
+            TokenRole::NftAddUri => "ESDTRoleNFTAddURI",
+            TokenRole::Transfer => "ESDTTransferRole"
+        }
+    }
+}
+
+/// Internal helper for building a call to the ESDT system smart contract
+fn esdt_sc_call(owner: &str, nonce: u64, value: &str, function: &str, args: &[Vec<u8>], network: Network) -> Result<UnsignedTransaction> {
+    let data = call_data(function, args);
+    contract_call(owner, nonce, &ElrondAddress::ESDT_SC, value, &data, ESDT_SC_CALL_GAS, network)
+}
+
+/// Check the decimals of a token
+fn check_decimals(decimals: u32) -> Result<()> {
+    if decimals > MAX_DECIMALS {
+        return Err(ElrondClientError::new(&format!("tokens have at most {} decimals", MAX_DECIMALS)));
+    }
+    Ok(())
+}
+
+/// Internal helper for the calls issuing tokens, whose arguments start with the name and ticker
+fn issue_call(owner: &str, nonce: u64, function: &str, name: &str, ticker: &str, args: Vec<Vec<u8>>, network: Network) -> Result<UnsignedTransaction> {
+    check_name(name)?;
+    check_ticker(ticker)?;
+    let mut all_args = vec![name.as_bytes().to_vec(), ticker.as_bytes().to_vec()];
+    all_args.extend(args);
+    esdt_sc_call(owner, nonce, ISSUE_FEE, function, &all_args, network)
+}
+
+/// Issue a fungible token named `name` with the given ticker and decimals, minting
+/// `initial_supply` (in human readable form, e.g., "1000.5") to `owner`. The 0.05 eGLD issuance
+/// fee is attached.
+#[allow(clippy::too_many_arguments)]
+pub fn issue_fungible(owner: &str, nonce: u64, name: &str, ticker: &str, initial_supply: &str, decimals: u32, flags: TokenFlags, network: Network) -> Result<UnsignedTransaction> {
+    check_decimals(decimals)?;
+    let initial_supply = TokenAmount::from_human(initial_supply, decimals, ticker)?;
+    let mut args = vec![biguint_arg(&initial_supply.base_units())?, u64_arg(decimals as u64)];
+    args.extend(flags.args(true));
+    issue_call(owner, nonce, "issue", name, ticker, args, network)
+}
+
+/// Issue a semi-fungible (SFT) collection. The 0.05 eGLD issuance fee is attached.
+pub fn issue_semi_fungible(owner: &str, nonce: u64, name: &str, ticker: &str, flags: TokenFlags, network: Network) -> Result<UnsignedTransaction> {
+    issue_call(owner, nonce, "issueSemiFungible", name, ticker, flags.args(false), network)
+}
+
+/// Issue a non-fungible (NFT) collection. The 0.05 eGLD issuance fee is attached.
+pub fn issue_non_fungible(owner: &str, nonce: u64, name: &str, ticker: &str, flags: TokenFlags, network: Network) -> Result<UnsignedTransaction> {
+    issue_call(owner, nonce, "issueNonFungible", name, ticker, flags.args(false), network)
+}
+
+/// Register a MetaESDT collection, whose tokens have decimals. The 0.05 eGLD issuance fee is
+/// attached.
+pub fn register_meta_esdt(owner: &str, nonce: u64, name: &str, ticker: &str, decimals: u32, flags: TokenFlags, network: Network) -> Result<UnsignedTransaction> {
+    check_decimals(decimals)?;
+    let mut args = vec![u64_arg(decimals as u64)];
+    args.extend(flags.args(false));
+    issue_call(owner, nonce, "registerMetaESDT", name, ticker, args, network)
+}
+
+/// Internal helper for the calls about a token and an address
+fn token_address_call(owner: &str, nonce: u64, function: &str, token: &str, address: &str, extra_args: Vec<Vec<u8>>, network: Network) -> Result<UnsignedTransaction> {
+    check_identifier(token)?;
+    let mut args = vec![token.as_bytes().to_vec(), ElrondAddress::new(address)?.to_bytes().to_vec()];
+    args.extend(extra_args);
+    esdt_sc_call(owner, nonce, "0", function, &args, network)
+}
+
+/// Give special roles for `token` to `address`
+pub fn set_special_roles(owner: &str, nonce: u64, token: &str, address: &str, roles: &[TokenRole], network: Network) -> Result<UnsignedTransaction> {
+    let roles = roles.iter().map(|role| role.name().as_bytes().to_vec()).collect();
+    token_address_call(owner, nonce, "setSpecialRole", token, address, roles, network)
+}
+
+/// Take special roles for `token` back from `address`
+pub fn unset_special_roles(owner: &str, nonce: u64, token: &str, address: &str, roles: &[TokenRole], network: Network) -> Result<UnsignedTransaction> {
+    let roles = roles.iter().map(|role| role.name().as_bytes().to_vec()).collect();
+    token_address_call(owner, nonce, "unSetSpecialRole", token, address, roles, network)
+}
+
+/// Internal helper for local mints and burns, sent by the holder of the role to itself
+fn local_supply_change(holder: &str, nonce: u64, function: &str, amount: &TokenAmount, network: Network) -> Result<UnsignedTransaction> {
+    check_identifier(amount.identifier())?;
+    let data = call_data(function, &[amount.identifier().as_bytes().to_vec(), biguint_arg(&amount.base_units())?]);
+    let holder_address = ElrondAddress::new(holder)?;
+    contract_call(holder, nonce, &holder_address, "0", &data, LOCAL_MINT_BURN_GAS, network)
+}
+
+/// Mint `amount` of a fungible token, which `holder` needs the `LocalMint` role for
+pub fn local_mint(holder: &str, nonce: u64, amount: &TokenAmount, network: Network) -> Result<UnsignedTransaction> {
+    local_supply_change(holder, nonce, "ESDTLocalMint", amount, network)
+}
+
+/// Burn `amount` of a fungible token, which `holder` needs the `LocalBurn` role for
+pub fn local_burn(holder: &str, nonce: u64, amount: &TokenAmount, network: Network) -> Result<UnsignedTransaction> {
+    local_supply_change(holder, nonce, "ESDTLocalBurn", amount, network)
+}
+
+/// Freeze the `token` balance of `address`, preventing transfers (requires `can_freeze`)
+pub fn freeze(owner: &str, nonce: u64, token: &str, address: &str, network: Network) -> Result<UnsignedTransaction> {
+    token_address_call(owner, nonce, "freeze", token, address, Vec::new(), network)
+}
+
+/// Unfreeze the `token` balance of `address`
+pub fn unfreeze(owner: &str, nonce: u64, token: &str, address: &str, network: Network) -> Result<UnsignedTransaction> {
+    token_address_call(owner, nonce, "unFreeze", token, address, Vec::new(), network)
+}
+
+/// Wipe the frozen `token` balance of `address` (requires `can_wipe`)
+pub fn wipe(owner: &str, nonce: u64, token: &str, address: &str, network: Network) -> Result<UnsignedTransaction> {
+    token_address_call(owner, nonce, "wipe", token, address, Vec::new(), network)
+}
+
+/// Transfer the management of `token` to `new_owner` (requires `can_change_owner`)
+pub fn transfer_ownership(owner: &str, nonce: u64, token: &str, new_owner: &str, network: Network) -> Result<UnsignedTransaction> {
+    token_address_call(owner, nonce, "transferOwnership", token, new_owner, Vec::new(), network)
+}
+
+/// Pause every transfer of `token` (requires `can_pause`)
+pub fn pause(owner: &str, nonce: u64, token: &str, network: Network) -> Result<UnsignedTransaction> {
+    check_identifier(token)?;
+    esdt_sc_call(owner, nonce, "0", "pause", &[token.as_bytes().to_vec()], network)
+}
+
+/// Resume the transfers of a paused `token`
+pub fn unpause(owner: &str, nonce: u64, token: &str, network: Network) -> Result<UnsignedTransaction> {
+    check_identifier(token)?;
+    esdt_sc_call(owner, nonce, "0", "unPause", &[token.as_bytes().to_vec()], network)
+}
+
+/// Gas consumed by creating an NFT, on top of the cost of its data and storage, as set by the
+/// erdjs token management factory (`gasLimitEsdtNftCreate`)
+const NFT_CREATE_GAS: u64 = 3000000;
+/// Gas consumed by the other NFT operations, on top of the cost of their data, as set by the erdjs
+/// token management factory (`gasLimitEsdtNftAddQuantity`, `gasLimitEsdtNftBurn` and
+/// `gasLimitEsdtNftUpdateAttributes`)
+const NFT_OPERATION_GAS: u64 = 1000000;
+/// Gas consumed by each byte of NFT metadata stored on chain when creating an NFT, as set by the
+/// erdjs token management factory (`gasLimitStorePerByte`)
+const GAS_PER_STORED_BYTE: u64 = 10000;
+/// Royalties are expressed in basis points, out of this total
+pub(crate) const MAX_ROYALTIES: u32 = 10000;
+
+/// Metadata of a new NFT, SFT or MetaESDT
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NftMetadata {
+    pub name: String,
+    /// Royalties in basis points, out of 10000
+    pub royalties: u32,
+    /// Hash of the content, e.g., of the image
+    pub hash: Vec<u8>,
+    /// Arbitrary attributes, e.g., "metadata:ipfsCID/1.json;tags:art"
+    pub attributes: Vec<u8>,
+    /// Links to the content, e.g., the image
+    pub uris: Vec<String>
+}
+
+/// Internal helper for NFT operations, sent by the holder of the role to itself
+fn nft_operation(holder: &str, nonce: u64, function: &str, args: &[Vec<u8>], gas: u64, network: Network) -> Result<UnsignedTransaction> {
+    let holder_address = ElrondAddress::new(holder)?;
+    contract_call(holder, nonce, &holder_address, "0", &call_data(function, args), gas, network)
+}
+
+/// Create `quantity` tokens of a new NFT, SFT or MetaESDT in `collection`, which `creator` needs
+/// the `NftCreate` role for. NFTs have a quantity of 1.
+pub fn nft_create(creator: &str, nonce: u64, collection: &str, quantity: u128, metadata: &NftMetadata, network: Network) -> Result<UnsignedTransaction> {
+    check_identifier(collection)?;
+    if metadata.royalties > MAX_ROYALTIES {
+        return Err(ElrondClientError::new(&format!("royalties are at most {} basis points", MAX_ROYALTIES)));
+    }
+    if quantity == 0 {
+        return Err(ElrondClientError::new("cannot create a zero quantity"));
+    }
+    let mut args = vec![
+        collection.as_bytes().to_vec(),
+        biguint_arg(&quantity.to_string())?,
+        metadata.name.as_bytes().to_vec(),
+        u64_arg(metadata.royalties as u64),
+        metadata.hash.clone(),
+        metadata.attributes.clone()
+    ];
+    args.extend(metadata.uris.iter().map(|uri| uri.as_bytes().to_vec()));
+    let stored_bytes = metadata.name.len()
+        + metadata.hash.len()
+        + metadata.attributes.len()
+        + metadata.uris.iter().map(String::len).sum::<usize>();
+    let gas = NFT_CREATE_GAS + GAS_PER_STORED_BYTE * stored_bytes as u64;
+    nft_operation(creator, nonce, "ESDTNFTCreate", &args, gas, network)
+}
+
+/// First arguments of the operations on an existing token: its collection and nonce
+fn nft_args(collection: &str, token_nonce: u64) -> Result<Vec<Vec<u8>>> {
+    check_identifier(collection)?;
+    Ok(vec![collection.as_bytes().to_vec(), u64_arg(token_nonce)])
+}
+
+/// Mint `quantity` more tokens of an SFT or MetaESDT, which `holder` needs the `NftAddQuantity`
+/// role for
+pub fn nft_add_quantity(holder: &str, nonce: u64, collection: &str, token_nonce: u64, quantity: u128, network: Network) -> Result<UnsignedTransaction> {
+    let mut args = nft_args(collection, token_nonce)?;
+    args.push(biguint_arg(&quantity.to_string())?);
+    nft_operation(holder, nonce, "ESDTNFTAddQuantity", &args, NFT_OPERATION_GAS, network)
+}
+
+/// Burn `quantity` tokens of an NFT, SFT or MetaESDT, which `holder` needs the `NftBurn` role for
+pub fn nft_burn(holder: &str, nonce: u64, collection: &str, token_nonce: u64, quantity: u128, network: Network) -> Result<UnsignedTransaction> {
+    let mut args = nft_args(collection, token_nonce)?;
+    args.push(biguint_arg(&quantity.to_string())?);
+    nft_operation(holder, nonce, "ESDTNFTBurn", &args, NFT_OPERATION_GAS, network)
+}
+
+/// Replace the attributes of a token, which `holder` needs the `NftUpdateAttributes` role for
+pub fn nft_update_attributes(holder: &str, nonce: u64, collection: &str, token_nonce: u64, attributes: &[u8], network: Network) -> Result<UnsignedTransaction> {
+    let mut args = nft_args(collection, token_nonce)?;
+    args.push(attributes.to_vec());
+    nft_operation(holder, nonce, "ESDTNFTUpdateAttributes", &args, NFT_OPERATION_GAS, network)
+}
+
+/// Add URIs to a token, which `holder` needs the `NftAddUri` role for
+pub fn nft_add_uris(holder: &str, nonce: u64, collection: &str, token_nonce: u64, uris: &[&str], network: Network) -> Result<UnsignedTransaction> {
+    if uris.is_empty() {
+        return Err(ElrondClientError::new("at least one URI is required"));
+    }
+    let mut args = nft_args(collection, token_nonce)?;
+    args.extend(uris.iter().map(|uri| uri.as_bytes().to_vec()));
+    nft_operation(holder, nonce, "ESDTNFTAddURI", &args, NFT_OPERATION_GAS, network)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collection_of, nft_identifier, parse_token_properties, parse_token_roles};
+    use super::{issue_fungible, register_meta_esdt, set_special_roles, local_mint, freeze, pause};
+    use super::{nft_create, nft_add_quantity, nft_burn, nft_update_attributes, nft_add_uris};
+    use super::{TokenFlags, TokenRole, NftMetadata};
+    use super::super::{ElrondAddress, TokenAmount, UnsignedTransaction, Network};
+
+    const OWNER: &str = "erd146apxa83wr7paz3gsg07dhcpg98ascjtpg9p8l8g5rpmg6chhchq9ccvmc";
+    const HOLDER: &str = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+
+    fn data_of(tx: &UnsignedTransaction) -> String {
+        String::from_utf8(tx.data().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn collection_drops_nonce() {
+        assert_eq!(collection_of("NFT-123456-0a"), "NFT-123456");
+        assert_eq!(collection_of("WEGLD-bd4d79"), "WEGLD-bd4d79");
+        assert_eq!(nft_identifier("NFT-123456", 10), "NFT-123456-0a");
+        assert_eq!(nft_identifier("NFT-123456", 256), "NFT-123456-0100");
+    }
+
+    #[test]
+    fn parse_properties() {
+        let fields: Vec<Vec<u8>> = vec![
+            b"WrappedEGLD".to_vec(),
+            b"FungibleESDT".to_vec(),
+            vec![0u8; 32],
+            b"1000000000000000000000".to_vec(),
+            b"0".to_vec(),
+            b"NumDecimals-18".to_vec(),
+            b"IsPaused-false".to_vec(),
+            b"CanUpgrade-true".to_vec(),
+            b"CanMint-true".to_vec(),
+            b"CanBurn-false".to_vec()
+        ];
+        let properties = parse_token_properties("WEGLD-bd4d79", &fields).unwrap();
+        assert_eq!(properties.name, "WrappedEGLD");
+        assert_eq!(properties.owner.to_string(), "erd1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqq6gq4hu");
+        assert!(properties.owner.is_smart_contract());
+        assert_eq!(properties.decimals, 18);
+        assert_eq!(properties.supply.to_string(), "1000");
+        assert!(!properties.is_paused);
+        assert!(properties.can_upgrade && properties.can_mint);
+        assert!(!properties.can_burn && !properties.can_freeze);
+    }
+
+    #[test]
+    fn parse_roles() {
+        let fields = vec![
+            b"erd1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqq6gq4hu:ESDTRoleLocalMint,ESDTRoleLocalBurn".to_vec()
+        ];
+        let roles = parse_token_roles(&fields).unwrap();
+        assert_eq!(roles[0].address.to_string(), "erd1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqq6gq4hu");
+        assert_eq!(roles[0].roles, vec!["ESDTRoleLocalMint", "ESDTRoleLocalBurn"]);
+    }
+
+    #[test]
+    fn issue_tokens() {
+        let flags = TokenFlags { can_freeze: true, can_mint: true, can_upgrade: true, ..TokenFlags::default() };
+        let tx = issue_fungible(OWNER, 1, "AliceCoin", "ALC", "1000", 6, flags, Network::MainNet).unwrap();
+        let data = data_of(&tx);
+        assert_eq!(data, format!(
+            "issue@{}@{}@3b9aca00@06@{}@{}@{}@{}@{}@{}@{}@{}@{}@{}@{}@{}@{}@{}@{}@{}",
+            hex::encode("AliceCoin"), hex::encode("ALC"),
+            hex::encode("canFreeze"), hex::encode("true"),
+            hex::encode("canWipe"), hex::encode("false"),
+            hex::encode("canPause"), hex::encode("false"),
+            hex::encode("canMint"), hex::encode("true"),
+            hex::encode("canBurn"), hex::encode("false"),
+            hex::encode("canChangeOwner"), hex::encode("false"),
+            hex::encode("canUpgrade"), hex::encode("true"),
+            hex::encode("canAddSpecialRoles"), hex::encode("false")
+        ));
+        assert_eq!(tx.receiver(), ElrondAddress::ESDT_SC.to_string());
+        assert_eq!(tx.value().unwrap().to_human().to_string(), "0.05");
+        assert_eq!(tx.gas_limit(), 50000 + 1500 * data.len() as u64 + 60000000);
+        let tx = register_meta_esdt(OWNER, 2, "LockedAlc", "LALC", 18, TokenFlags::default(), Network::MainNet).unwrap();
+        assert!(data_of(&tx).starts_with(&format!("registerMetaESDT@{}@{}@12@", hex::encode("LockedAlc"), hex::encode("LALC"))));
+        assert!(data_of(&tx).contains(&hex::encode("canTransferNFTCreateRole")));
+    }
+
+    #[test]
+    fn issue_validation() {
+        let flags = TokenFlags::default();
+        assert!(issue_fungible(OWNER, 1, "AliceCoin", "ALC", "1", 0, flags, Network::MainNet).is_ok());
+        assert!(issue_fungible(OWNER, 1, "Alice Coin", "ALC", "1", 0, flags, Network::MainNet).is_err());
+        assert!(issue_fungible(OWNER, 1, "AC", "ALC", "1", 0, flags, Network::MainNet).is_err());
+        assert!(issue_fungible(OWNER, 1, "AliceCoin", "alc", "1", 0, flags, Network::MainNet).is_err());
+        assert!(issue_fungible(OWNER, 1, "AliceCoin", "ALICECOIN12", "1", 0, flags, Network::MainNet).is_err());
+        assert!(issue_fungible(OWNER, 1, "AliceCoin", "ALC", "1.5", 0, flags, Network::MainNet).is_err());
+        assert!(issue_fungible(OWNER, 1, "AliceCoin", "ALC", "1", 19, flags, Network::MainNet).is_err());
+        assert!(register_meta_esdt(OWNER, 1, "AliceCoin", "ALC", 19, flags, Network::MainNet).is_err());
+        assert!(pause(OWNER, 1, "ALC", Network::MainNet).is_err());
+        assert!(pause(OWNER, 1, "ALC-12345g", Network::MainNet).is_err());
+    }
+
+    #[test]
+    fn manage_tokens() {
+        let holder_hex = ElrondAddress::new(HOLDER).unwrap().to_hex();
+        let roles = [TokenRole::LocalMint, TokenRole::LocalBurn];
+        let tx = set_special_roles(OWNER, 1, "ALC-1a2b3c", HOLDER, &roles, Network::MainNet).unwrap();
+        assert_eq!(data_of(&tx), format!(
+            "setSpecialRole@{}@{}@{}@{}",
+            hex::encode("ALC-1a2b3c"), holder_hex, hex::encode("ESDTRoleLocalMint"), hex::encode("ESDTRoleLocalBurn")
+        ));
+        let tx = freeze(OWNER, 2, "ALC-1a2b3c", HOLDER, Network::MainNet).unwrap();
+        assert_eq!(data_of(&tx), format!("freeze@{}@{}", hex::encode("ALC-1a2b3c"), holder_hex));
+        let amount = TokenAmount::from_human("2.5", 6, "ALC-1a2b3c").unwrap();
+        let tx = local_mint(HOLDER, 1, &amount, Network::MainNet).unwrap();
+        let data = data_of(&tx);
+        assert_eq!(data, format!("ESDTLocalMint@{}@2625a0", hex::encode("ALC-1a2b3c")));
+        assert_eq!(tx.receiver(), HOLDER);
+        assert_eq!(tx.gas_limit(), 50000 + 1500 * data.len() as u64 + 300000);
+    }
+
+    #[test]
+    fn create_and_update_nfts() {
+        let metadata = NftMetadata {
+            name: "Art #1".to_string(),
+            royalties: 750,
+            hash: vec![0xab; 4],
+            attributes: b"tags:art".to_vec(),
+            uris: vec!["https://img.local/1.png".to_string(), "https://img.local/1.json".to_string()]
+        };
+        let tx = nft_create(HOLDER, 3, "ART-1a2b3c", 1, &metadata, Network::MainNet).unwrap();
+        let data = data_of(&tx);
+        assert_eq!(data, format!(
+            "ESDTNFTCreate@{}@01@{}@02ee@abababab@{}@{}@{}",
+            hex::encode("ART-1a2b3c"), hex::encode("Art #1"), hex::encode("tags:art"),
+            hex::encode("https://img.local/1.png"), hex::encode("https://img.local/1.json")
+        ));
+        assert_eq!(tx.receiver(), HOLDER);
+        // erdjs gas limits: 3M for creating, 10000 per stored byte of metadata, on top of the data
+        assert_eq!(tx.gas_limit(), 3965500);
+        let too_high = NftMetadata { royalties: 10001, ..metadata.clone() };
+        assert!(nft_create(HOLDER, 3, "ART-1a2b3c", 1, &too_high, Network::MainNet).is_err());
+        assert!(nft_create(HOLDER, 3, "ART-1a2b3c", 0, &metadata, Network::MainNet).is_err());
+
+        let tx = nft_add_quantity(HOLDER, 4, "SFT-1a2b3c", 10, 500, Network::MainNet).unwrap();
+        assert_eq!(data_of(&tx), format!("ESDTNFTAddQuantity@{}@0a@01f4", hex::encode("SFT-1a2b3c")));
+        // 1M for the other operations, on top of the data
+        assert_eq!(tx.gas_limit(), 1120500);
+        assert_eq!(nft_burn(HOLDER, 4, "SFT-1a2b3c", 10, 500, Network::MainNet).unwrap().gas_limit(), 1110000);
+        let tx = nft_update_attributes(HOLDER, 5, "ART-1a2b3c", 1, b"tags:gold", Network::MainNet).unwrap();
+        let data = data_of(&tx);
+        assert_eq!(data, format!("ESDTNFTUpdateAttributes@{}@01@{}", hex::encode("ART-1a2b3c"), hex::encode("tags:gold")));
+        assert_eq!(tx.gas_limit(), 1149000);
+        assert!(nft_add_uris(HOLDER, 6, "ART-1a2b3c", 1, &[], Network::MainNet).is_err());
+    }
+}
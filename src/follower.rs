@@ -0,0 +1,535 @@
+//! Follow final hyperblocks to stream the transfers touching a set of addresses, e.g., for
+//! accounting. A `Follower` walks hyperblocks from a checkpoint, decodes eGLD, ESDT and NFT
+//! transfers from transactions and smart contract results, and saves its position in a
+//! `CursorStore` once the events of each hyperblock have been delivered. After a restart, it
+//! resumes from the saved position, so events are delivered at least once.
+
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use num_bigint::BigUint;
+use serde::{Serialize, Deserialize};
+use super::esdt::nft_identifier;
+use super::{Client, Transport, FinalHyperblocks, ElrondAddress, BlockTransaction, Hyperblock, ElrondClientError, Result};
+
+/// Number of hyperblocks during which the smart contract results delivering a cross-shard ESDT
+/// transfer are recognized as duplicates of the transfer
+const PENDING_HYPERBLOCKS: u64 = 100;
+
+/// Asset moved by a transfer
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Asset {
+    Egld,
+    /// Fungible ESDT token, e.g., "WEGLD-bd4d79"
+    Esdt(String),
+    /// NFT, SFT or MetaESDT token of a collection
+    Nft { collection: String, nonce: u64 }
+}
+
+impl Asset {
+    /// Identifier of the asset, e.g., "EGLD", "WEGLD-bd4d79" or "NFT-123456-0a"
+    pub fn identifier(&self) -> String {
+        match self {
+            Asset::Egld => "EGLD".to_string(),
+            Asset::Esdt(identifier) => identifier.clone(),
+            Asset::Nft { collection, nonce } => nft_identifier(collection, *nonce)
+        }
+    }
+}
+
+/// Transfer of an asset between two addresses
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Transfer {
+    pub sender: ElrondAddress,
+    pub receiver: ElrondAddress,
+    pub asset: Asset,
+    /// Amount in base units, e.g., denominated eGLD
+    pub amount: String
+}
+
+/// Transfer found in a final hyperblock
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferEvent {
+    pub hyperblock_nonce: u64,
+    /// Timestamp of the hyperblock, in seconds
+    pub timestamp: u64,
+    /// Hash of the transaction or smart contract result holding the transfer
+    pub tx_hash: String,
+    /// Hash of the transaction sent by an account that led to the transfer
+    pub original_tx_hash: String,
+    pub transfer: Transfer
+}
+
+/// Decode a hex argument of a transaction data field as an unsigned integer
+fn integer(arg: &[u8]) -> String {
+    BigUint::from_bytes_be(arg).to_string()
+}
+
+/// Decode a token nonce argument
+fn token_nonce(arg: &[u8]) -> Option<u64> {
+    if arg.len() > 8 {
+        return None;
+    }
+    Some(arg.iter().fold(0, |nonce, byte| (nonce << 8) | *byte as u64))
+}
+
+/// Asset designated by a token identifier and nonce argument, fungible when the nonce is zero
+fn asset(identifier: &[u8], nonce: &[u8]) -> Option<Asset> {
+    let identifier = String::from_utf8(identifier.to_vec()).ok()?;
+    Some(match token_nonce(nonce)? {
+        0 => Asset::Esdt(identifier),
+        nonce => Asset::Nft { collection: identifier, nonce }
+    })
+}
+
+/// Decode the ESDT transfers of a data field. Transfers of NFTs (and of several tokens) sent by
+/// accounts are sent to themselves and name the destination as an argument, whereas smart
+/// contract results delivering them are sent to the destination.
+fn esdt_transfers(tx: &BlockTransaction) -> Option<Vec<Transfer>> {
+    let data = String::from_utf8(tx.data.clone()).ok()?;
+    let mut parts = data.split('@');
+    let function = parts.next()?;
+    let args = parts.map(hex::decode).collect::<std::result::Result<Vec<Vec<u8>>, _>>().ok()?;
+    let to_self = tx.sender == tx.receiver;
+    let transfer = |receiver: &ElrondAddress, asset: Asset, amount: &[u8]| Transfer {
+        sender: tx.sender.clone(),
+        receiver: receiver.clone(),
+        asset,
+        amount: integer(amount)
+    };
+    match function {
+        "ESDTTransfer" if args.len() >= 2 => {
+            let identifier = String::from_utf8(args[0].clone()).ok()?;
+            Some(vec![transfer(&tx.receiver, Asset::Esdt(identifier), &args[1])])
+        }
+        "ESDTNFTTransfer" if args.len() >= 3 => {
+            let receiver = if to_self { ElrondAddress::from_bytes(args.get(3)?).ok()? } else { tx.receiver.clone() };
+            Some(vec![transfer(&receiver, asset(&args[0], &args[1])?, &args[2])])
+        }
+        "MultiESDTNFTTransfer" => {
+            let (receiver, args) = if to_self {
+                (ElrondAddress::from_bytes(args.first()?).ok()?, &args[1..])
+            } else {
+                (tx.receiver.clone(), &args[..])
+            };
+            let count = token_nonce(args.first()?)? as usize;
+            let tokens = args.get(1..1 + 3 * count)?;
+            tokens
+                .chunks(3)
+                .map(|token| Some(transfer(&receiver, asset(&token[0], &token[1])?, &token[2])))
+                .collect()
+        }
+        _ => None
+    }
+}
+
+/// Decode the transfers made by a transaction or smart contract result: its eGLD value, and the
+/// ESDT and NFT transfers of its data field. Failed transactions transfer nothing.
+pub fn decode_transfers(tx: &BlockTransaction) -> Vec<Transfer> {
+    if tx.kind == "invalid" || tx.status == "fail" || tx.status == "invalid" {
+        return Vec::new();
+    }
+    let mut transfers = Vec::new();
+    let value = tx.value.to_string();
+    if value != "0" {
+        transfers.push(Transfer {
+            sender: tx.sender.clone(),
+            receiver: tx.receiver.clone(),
+            asset: Asset::Egld,
+            amount: value
+        });
+    }
+    transfers.extend(esdt_transfers(tx).unwrap_or_default());
+    transfers
+}
+
+/// Transfers of a cross-shard transaction, not yet delivered by smart contract results
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PendingTransfers {
+    hyperblock_nonce: u64,
+    transfers: Vec<Transfer>
+}
+
+/// Position of a `Follower`: the nonce of the next hyperblock to process, along with the
+/// cross-shard transfers whose smart contract results are still expected, so that they are not
+/// reported twice after a restart
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    pub next_nonce: u64,
+    #[serde(default)]
+    pending: BTreeMap<String, PendingTransfers>
+}
+
+impl Checkpoint {
+    /// Checkpoint of a follower starting at the hyperblock with nonce `next_nonce`
+    pub fn new(next_nonce: u64) -> Self {
+        Self { next_nonce, pending: BTreeMap::new() }
+    }
+}
+
+/// Where a `Follower` saves its checkpoint
+pub trait CursorStore {
+    /// Load the saved checkpoint, if any
+    fn load(&self) -> Result<Option<Checkpoint>>;
+    /// Save the checkpoint, once the events of the hyperblocks before it have been delivered
+    fn save(&mut self, checkpoint: &Checkpoint) -> Result<()>;
+}
+
+/// Cursor kept in memory, lost on restart
+#[derive(Clone, Debug, Default)]
+pub struct MemoryCursor(pub Option<Checkpoint>);
+
+impl CursorStore for MemoryCursor {
+    fn load(&self) -> Result<Option<Checkpoint>> {
+        Ok(self.0.clone())
+    }
+    fn save(&mut self, checkpoint: &Checkpoint) -> Result<()> {
+        self.0 = Some(checkpoint.clone());
+        Ok(())
+    }
+}
+
+/// Cursor saved as JSON in a file, replaced atomically
+#[derive(Clone, Debug)]
+pub struct FileCursor {
+    path: PathBuf
+}
+
+impl FileCursor {
+    /// Create a cursor saved at `path`
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl CursorStore for FileCursor {
+    fn load(&self) -> Result<Option<Checkpoint>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&self.path).map_err(|e| {
+            ElrondClientError::new(&format!("could not read cursor '{}': {}", self.path.display(), e))
+        })?;
+        serde_json::from_str(&contents).map(Some).map_err(|_| {
+            ElrondClientError::new(&format!("cursor '{}' does not hold a checkpoint", self.path.display()))
+        })
+    }
+    fn save(&mut self, checkpoint: &Checkpoint) -> Result<()> {
+        let contents = serde_json::to_string(checkpoint).map_err(|_| {
+            ElrondClientError::new("could not serialize checkpoint")
+        })?;
+        let temporary = self.path.with_extension("tmp");
+        fs::write(&temporary, contents)
+            .and_then(|_| fs::rename(&temporary, &self.path))
+            .map_err(|e| {
+                ElrondClientError::new(&format!("could not save cursor '{}': {}", self.path.display(), e))
+            })
+    }
+}
+
+/// Follows final hyperblocks and delivers the transfers touching the watched addresses, through
+/// `poll` (with a callback) or as an iterator
+pub struct Follower<'a, T: Transport, C: CursorStore> {
+    client: &'a Client<T>,
+    cursor: C,
+    watched: HashSet<ElrondAddress>,
+    // checkpoint of the last hyperblock whose events were all delivered
+    checkpoint: Checkpoint,
+    hyperblocks: FinalHyperblocks<'a, T>,
+    // events of the current hyperblock not yet yielded by the iterator
+    buffered: VecDeque<TransferEvent>,
+    // checkpoint to save once the buffered events have been consumed
+    unsaved: Option<Checkpoint>
+}
+
+impl<'a, T: Transport, C: CursorStore> Follower<'a, T, C> {
+    /// Create a follower resuming from the position saved in `cursor`, or starting at the
+    /// hyperblock with nonce `start` if nothing was saved
+    pub fn new(client: &'a Client<T>, cursor: C, start: u64) -> Result<Self> {
+        let checkpoint = cursor.load()?.unwrap_or_else(|| Checkpoint::new(start));
+        Ok(Self {
+            client,
+            cursor,
+            watched: HashSet::new(),
+            hyperblocks: client.final_hyperblocks(checkpoint.next_nonce),
+            checkpoint,
+            buffered: VecDeque::new(),
+            unsaved: None
+        })
+    }
+
+    /// Deliver the transfers touching `address`. Without watched addresses, every transfer is
+    /// delivered.
+    pub fn watch(mut self, address: &str) -> Result<Self> {
+        self.watched.insert(ElrondAddress::new(address)?);
+        Ok(self)
+    }
+
+    /// Nonce of the next hyperblock to process
+    pub fn next_nonce(&self) -> u64 {
+        self.checkpoint.next_nonce
+    }
+
+    /// Cursor store of the follower
+    pub fn cursor(&self) -> &C {
+        &self.cursor
+    }
+
+    /// Whether a transfer concerns the watched addresses
+    fn is_watched(&self, transfer: &Transfer) -> bool {
+        self.watched.is_empty()
+            || self.watched.contains(&transfer.sender)
+            || self.watched.contains(&transfer.receiver)
+    }
+
+    /// Fetch the next final hyperblock, if the chain has finalized it. After a failed delivery,
+    /// the hyperblocks are walked again from the checkpoint.
+    fn next_hyperblock(&mut self) -> Result<Option<Hyperblock>> {
+        if self.hyperblocks.next_nonce() != self.checkpoint.next_nonce {
+            self.hyperblocks = self.client.final_hyperblocks(self.checkpoint.next_nonce);
+        }
+        self.hyperblocks.next().transpose()
+    }
+
+    /// Decode the events of a hyperblock, along with the checkpoint following it. Cross-shard ESDT
+    /// transfers appear both in the transaction of the sender and in the smart contract result
+    /// delivering the tokens, and are only reported once. The checkpoint of the follower is left
+    /// untouched, so that the hyperblock can be processed again if delivering its events fails.
+    fn events(&self, hyperblock: &Hyperblock) -> (Vec<TransferEvent>, Checkpoint) {
+        let mut pending = self.checkpoint.pending.clone();
+        pending.retain(|_, transfers| {
+            transfers.hyperblock_nonce.saturating_add(PENDING_HYPERBLOCKS) >= hyperblock.nonce
+                && !transfers.transfers.is_empty()
+        });
+        let mut events = Vec::new();
+        for tx in &hyperblock.transactions {
+            let original_tx_hash = tx.original_transaction_hash.clone().unwrap_or_else(|| tx.hash.clone());
+            for transfer in decode_transfers(tx) {
+                if tx.kind == "unsigned" && transfer.asset != Asset::Egld {
+                    if let Some(delivered) = pending.get_mut(&original_tx_hash) {
+                        if let Some(index) = delivered.transfers.iter().position(|pending| *pending == transfer) {
+                            delivered.transfers.remove(index);
+                            continue;
+                        }
+                    }
+                }
+                if tx.kind == "normal" && transfer.asset != Asset::Egld && tx.source_shard != tx.destination_shard {
+                    pending
+                        .entry(tx.hash.clone())
+                        .or_insert_with(|| PendingTransfers { hyperblock_nonce: hyperblock.nonce, transfers: Vec::new() })
+                        .transfers
+                        .push(transfer.clone());
+                }
+                if self.is_watched(&transfer) {
+                    events.push(TransferEvent {
+                        hyperblock_nonce: hyperblock.nonce,
+                        timestamp: hyperblock.timestamp,
+                        tx_hash: tx.hash.clone(),
+                        original_tx_hash: original_tx_hash.clone(),
+                        transfer
+                    });
+                }
+            }
+        }
+        (events, Checkpoint { next_nonce: hyperblock.nonce + 1, pending })
+    }
+
+    /// Process every final hyperblock not processed yet, calling `on_event` for each transfer.
+    /// The cursor is saved after the events of each hyperblock have been handled; if `on_event`
+    /// fails, the hyperblock is processed again by the next call. Returns the number of
+    /// hyperblocks processed.
+    pub fn poll<F: FnMut(&TransferEvent) -> Result<()>>(&mut self, mut on_event: F) -> Result<usize> {
+        let mut processed = 0;
+        while let Some(hyperblock) = self.next_hyperblock()? {
+            let (events, checkpoint) = self.events(&hyperblock);
+            for event in events {
+                on_event(&event)?;
+            }
+            self.cursor.save(&checkpoint)?;
+            self.checkpoint = checkpoint;
+            processed += 1;
+        }
+        Ok(processed)
+    }
+}
+
+impl<'a, T: Transport, C: CursorStore> Iterator for Follower<'a, T, C> {
+    type Item = Result<TransferEvent>;
+
+    /// Yield the next transfer, ending once caught up with the chain. The cursor is saved when
+    /// the events of a hyperblock have all been consumed, i.e., when asking for the next one.
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.buffered.pop_front() {
+                return Some(Ok(event));
+            }
+            if let Some(checkpoint) = self.unsaved.take() {
+                if let Err(e) = self.cursor.save(&checkpoint) {
+                    self.unsaved = Some(checkpoint);
+                    return Some(Err(e));
+                }
+                self.checkpoint = checkpoint;
+            }
+            let hyperblock = match self.next_hyperblock() {
+                Ok(Some(hyperblock)) => hyperblock,
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e))
+            };
+            let (events, checkpoint) = self.events(&hyperblock);
+            self.buffered = events.into();
+            self.unsaved = Some(checkpoint);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use super::{decode_transfers, Asset, Follower, MemoryCursor, FileCursor, CursorStore};
+    use super::super::{Client, MockTransport, BlockTransaction, ElrondAddress, ElrondClientError};
+
+    const ALICE: &str = "erd1qyu5wthldzr8wx5c9ucg8kjagg0jfs53s8nr3zpz3hypefsdd8ssycr6th";
+    const BOB: &str = "erd1spyavw0956vq68xj8y4tenjpq2wd5a9p2c6j8gsz7ztyrnpxrruqzu66jx";
+    const CAROL: &str = "erd1k2s324ww2g0yj38qn2ch2jwctdy8mnfxep94q9arncc6xecg3xaq6mjse8";
+
+    fn tx(kind: &str, hash: &str, sender: &str, receiver: &str, value: &str, data: &str) -> Value {
+        json!({
+            "type": kind, "hash": hash, "nonce": 1, "value": value, "sender": sender, "receiver": receiver,
+            "data": base64::encode(data), "sourceShard": 0, "destinationShard": 1, "status": "success"
+        })
+    }
+
+    fn hex_address(address: &str) -> String {
+        ElrondAddress::new(address).unwrap().to_hex()
+    }
+
+    fn hyperblock(nonce: u64, transactions: Vec<Value>) -> Value {
+        json!({"data": {"hyperblock": {
+            "hash": format!("h{}", nonce), "nonce": nonce, "round": nonce, "epoch": 1, "timestamp": 1000 + nonce,
+            "transactions": transactions
+        }}})
+    }
+
+    fn final_nonce(nonce: u64) -> Value {
+        json!({"data": {"status": {
+            "erd_current_round": nonce, "erd_epoch_number": 1, "erd_nonce": nonce, "erd_highest_final_nonce": nonce
+        }}})
+    }
+
+    #[test]
+    fn decode_esdt_and_nft_transfers() {
+        let decode = |value: Value| decode_transfers(&serde_json::from_value::<BlockTransaction>(value).unwrap());
+        let transfers = decode(tx("normal", "t1", ALICE, BOB, "5", &format!("ESDTTransfer@{}@0a", hex::encode("USDC-c76f1f"))));
+        assert_eq!(transfers.len(), 2);
+        assert_eq!(transfers[0].asset, Asset::Egld);
+        assert_eq!(transfers[1].asset, Asset::Esdt("USDC-c76f1f".to_string()));
+        assert_eq!(transfers[1].amount, "10");
+        let nft = format!("ESDTNFTTransfer@{}@0a@01@{}", hex::encode("NFT-123456"), hex_address(CAROL));
+        let transfers = decode(tx("normal", "t2", ALICE, ALICE, "0", &nft));
+        assert_eq!(transfers[0].receiver.to_string(), CAROL);
+        assert_eq!(transfers[0].asset.identifier(), "NFT-123456-0a");
+        let multi = format!(
+            "MultiESDTNFTTransfer@{}@02@{}@00@64@{}@05@01@{}",
+            hex_address(BOB), hex::encode("USDC-c76f1f"), hex::encode("NFT-123456"), hex::encode("buy")
+        );
+        let transfers = decode(tx("normal", "t3", ALICE, ALICE, "0", &multi));
+        assert_eq!(transfers.len(), 2);
+        assert!(transfers.iter().all(|transfer| transfer.receiver.to_string() == BOB));
+        assert_eq!(transfers[0].amount, "100");
+        assert_eq!(transfers[1].asset, Asset::Nft { collection: "NFT-123456".to_string(), nonce: 5 });
+        let mut failed = tx("normal", "t4", ALICE, BOB, "5", "");
+        failed["status"] = json!("fail");
+        assert!(decode(failed).is_empty());
+    }
+
+    #[test]
+    fn follower_delivers_watched_transfers_once() {
+        let client = Client::with_transport("https://api.elrond.com", MockTransport::new());
+        let esdt = format!("ESDTTransfer@{}@0a", hex::encode("USDC-c76f1f"));
+        let mut result = tx("unsigned", "r1", ALICE, BOB, "0", &esdt);
+        result["originalTransactionHash"] = json!("t1");
+        client.transport()
+            .push_json(&final_nonce(11))
+            .push_json(&hyperblock(10, vec![tx("normal", "t1", ALICE, BOB, "0", &esdt), tx("normal", "t2", CAROL, CAROL, "1", "")]))
+            .push_json(&hyperblock(11, vec![result, tx("unsigned", "r2", CAROL, BOB, "2", "")]))
+            .push_json(&final_nonce(11));
+        let mut follower = Follower::new(&client, MemoryCursor::default(), 10).unwrap().watch(BOB).unwrap();
+        let mut events = Vec::new();
+        assert_eq!(follower.poll(|event| { events.push(event.clone()); Ok(()) }).unwrap(), 2);
+        let hashes: Vec<&str> = events.iter().map(|event| event.tx_hash.as_str()).collect();
+        assert_eq!(hashes, vec!["t1", "r2"]);
+        assert_eq!(events[1].transfer.sender.to_string(), CAROL);
+        assert_eq!(follower.cursor().load().unwrap().unwrap().next_nonce, 12);
+    }
+
+    #[test]
+    fn failed_callback_does_not_report_results_twice() {
+        let client = Client::with_transport("https://api.elrond.com", MockTransport::new());
+        let esdt = format!("ESDTTransfer@{}@0a", hex::encode("USDC-c76f1f"));
+        let mut result = tx("unsigned", "r1", ALICE, BOB, "0", &esdt);
+        result["originalTransactionHash"] = json!("t1");
+        let results = hyperblock(11, vec![result, tx("unsigned", "r2", CAROL, BOB, "2", "")]);
+        client.transport()
+            .push_json(&final_nonce(11))
+            .push_json(&hyperblock(10, vec![tx("normal", "t1", ALICE, BOB, "0", &esdt)]))
+            .push_json(&results);
+        let mut follower = Follower::new(&client, MemoryCursor::default(), 10).unwrap().watch(BOB).unwrap();
+        let mut events = Vec::new();
+        let failing = follower.poll(|event| {
+            if event.tx_hash == "r2" {
+                return Err(ElrondClientError::new("could not record transfer"));
+            }
+            events.push(event.tx_hash.clone());
+            Ok(())
+        });
+        assert!(failing.is_err());
+        assert_eq!(follower.next_nonce(), 11);
+        client.transport().push_json(&final_nonce(11)).push_json(&results).push_json(&final_nonce(11));
+        assert_eq!(follower.poll(|event| { events.push(event.tx_hash.clone()); Ok(()) }).unwrap(), 1);
+        assert_eq!(events, vec!["t1", "r2"]);
+    }
+
+    #[test]
+    fn restart_does_not_report_results_twice() {
+        let path = std::env::temp_dir().join(format!("elrond-follower-restart-{}.cursor", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let client = Client::with_transport("https://api.elrond.com", MockTransport::new());
+        let esdt = format!("ESDTTransfer@{}@0a", hex::encode("USDC-c76f1f"));
+        let mut result = tx("unsigned", "r1", ALICE, BOB, "0", &esdt);
+        result["originalTransactionHash"] = json!("t1");
+        client.transport()
+            .push_json(&final_nonce(10))
+            .push_json(&hyperblock(10, vec![tx("normal", "t1", ALICE, BOB, "0", &esdt)]))
+            .push_json(&final_nonce(10))
+            .push_json(&final_nonce(11))
+            .push_json(&hyperblock(11, vec![result]))
+            .push_json(&final_nonce(11));
+        let mut events = Vec::new();
+        let mut follower = Follower::new(&client, FileCursor::new(&path), 10).unwrap().watch(BOB).unwrap();
+        follower.poll(|event| { events.push(event.tx_hash.clone()); Ok(()) }).unwrap();
+        let mut restarted = Follower::new(&client, FileCursor::new(&path), 0).unwrap().watch(BOB).unwrap();
+        assert_eq!(restarted.next_nonce(), 11);
+        assert_eq!(restarted.poll(|event| { events.push(event.tx_hash.clone()); Ok(()) }).unwrap(), 1);
+        assert_eq!(events, vec!["t1"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn follower_iterates_and_resumes_from_file() {
+        let path = std::env::temp_dir().join(format!("elrond-follower-{}.cursor", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let client = Client::with_transport("https://api.elrond.com", MockTransport::new());
+        client.transport()
+            .push_json(&final_nonce(5))
+            .push_json(&hyperblock(5, vec![tx("normal", "t1", ALICE, BOB, "1", ""), tx("normal", "t2", BOB, ALICE, "1", "")]))
+            .push_json(&final_nonce(5));
+        let follower = Follower::new(&client, FileCursor::new(&path), 5).unwrap();
+        let events: Vec<_> = follower.map(|event| event.unwrap().tx_hash).collect();
+        assert_eq!(events, vec!["t1", "t2"]);
+        let resumed = Follower::new(&client, FileCursor::new(&path), 0).unwrap();
+        assert_eq!(resumed.next_nonce(), 6);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,100 @@
+//! Guardians co-sign the transactions of the accounts they guard. An account registers a guardian
+//! with `set_guardian`, activates it with `guard_account` once the activation delay has passed,
+//! and can deactivate it with `unguard_account`. Transactions of a guarded account must carry the
+//! guardian (see `UnsignedTransaction::with_guardian`) and be co-signed by it
+//! (see `SignedTransaction::sign_as_guardian`).
+
+use super::transaction::{MIN_GAS_LIMIT, GAS_PER_DATA_BYTE};
+use super::{Network, UnsignedTransaction, ElrondAddress, Result};
+
+/// Gas consumed by guardian operations, on top of the cost of their data
+const GUARDIAN_OPERATION_GAS: u64 = 250000;
+
+/// Internal helper for building a guardian operation, sent by `account` to itself
+fn guardian_operation(account: &str, nonce: u64, data: &str, network: Network) -> Result<UnsignedTransaction> {
+    let gas_limit = MIN_GAS_LIMIT + GAS_PER_DATA_BYTE * data.len() as u64 + GUARDIAN_OPERATION_GAS;
+    Ok(UnsignedTransaction::new(nonce, "0", account, account, network)?
+        .with_data(data.as_bytes())
+        .with_gas_limit(gas_limit))
+}
+
+/// Register `guardian`, operated by the guardian service `service_id`, for `account`
+pub fn set_guardian(account: &str, nonce: u64, guardian: &str, service_id: &str, network: Network) -> Result<UnsignedTransaction> {
+    let guardian = ElrondAddress::new(guardian)?;
+    let data = format!("SetGuardian@{}@{}", guardian.to_hex(), hex::encode(service_id));
+    guardian_operation(account, nonce, &data, network)
+}
+
+/// Activate the guardian registered for `account`
+pub fn guard_account(account: &str, nonce: u64, network: Network) -> Result<UnsignedTransaction> {
+    guardian_operation(account, nonce, "GuardAccount", network)
+}
+
+/// Deactivate the guardian of `account`. Without the co-signature of the guardian (see
+/// `UnsignedTransaction::with_guardian`), this only takes effect after a delay.
+pub fn unguard_account(account: &str, nonce: u64, network: Network) -> Result<UnsignedTransaction> {
+    guardian_operation(account, nonce, "UnGuardAccount", network)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{set_guardian, guard_account, unguard_account};
+    use super::super::{Account, ElrondAddress, Network, SignedTransaction, OPTION_GUARDED};
+
+    const GUARDIAN: &str = "erd1qyu5wthldzr8wx5c9ucg8kjagg0jfs53s8nr3zpz3hypefsdd8ssycr6th";
+
+    fn owner() -> Account {
+        Account::from_string("a4b36a5d97176618b5a7fcc9228d2fd98ee2f14ddd3d6462ae03e40eb487d15b").unwrap()
+    }
+
+    #[test]
+    fn guardian_operations() {
+        let address = owner().address.to_string();
+        let tx = set_guardian(&address, 1, GUARDIAN, "MultiversXTCSService", Network::MainNet).unwrap();
+        let data = String::from_utf8(tx.data().unwrap()).unwrap();
+        let guardian_hex = ElrondAddress::new(GUARDIAN).unwrap().to_hex();
+        assert_eq!(data, format!("SetGuardian@{}@{}", guardian_hex, hex::encode("MultiversXTCSService")));
+        assert_eq!(tx.receiver(), address);
+        assert_eq!(tx.gas_limit(), 50000 + 1500 * data.len() as u64 + 250000);
+        let tx = guard_account(&address, 2, Network::MainNet).unwrap();
+        assert_eq!(tx.data().unwrap(), b"GuardAccount");
+        assert_eq!(tx.gas_limit(), 50000 + 1500 * 12 + 250000);
+        assert_eq!(unguard_account(&address, 3, Network::MainNet).unwrap().data().unwrap(), b"UnGuardAccount");
+    }
+
+    #[test]
+    fn guarded_transaction_is_co_signed() {
+        let guardian = Account::generate().unwrap();
+        let tx = guard_account(&owner().address.to_string(), 2, Network::MainNet)
+            .unwrap()
+            .with_guardian(&guardian.address.to_string())
+            .unwrap();
+        assert_eq!(tx.version(), 2);
+        assert_eq!(tx.options(), OPTION_GUARDED);
+        assert_eq!(tx.gas_limit(), 50000 + 1500 * 12 + 250000 + 50000);
+        let serialized = tx.serialize().unwrap();
+        assert!(serialized.ends_with(&format!(
+            "\"version\":2,\"options\":2,\"guardian\":\"{}\"}}", guardian.address
+        )));
+        let signed = tx.sign(&owner()).unwrap();
+        assert!(signed.clone().sign_as_guardian(&owner()).is_err());
+        // guarded transactions are only valid once co-signed
+        assert!(signed.verify().is_err());
+        let signed = signed.sign_as_guardian(&guardian).unwrap();
+        assert!(signed.verify().is_ok());
+        assert!(signed.serialize().unwrap().contains("\"guardianSignature\":"));
+    }
+
+    #[test]
+    fn guardian_signature_requires_guardian() {
+        let signed = guard_account(&owner().address.to_string(), 2, Network::MainNet)
+            .unwrap()
+            .sign(&owner())
+            .unwrap();
+        let mut json: serde_json::Value = serde_json::from_str(&signed.serialize().unwrap()).unwrap();
+        json["guardianSignature"] = signed.signature().into();
+        let tampered: SignedTransaction = serde_json::from_value(json).unwrap();
+        assert!(signed.verify().is_ok());
+        assert!(tampered.verify().is_err());
+    }
+}
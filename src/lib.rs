@@ -3,15 +3,15 @@
 //!
 //! ### Example interaction:
 //!
-//! ```
-//! use elrond_rust::{Client, Account, UnsignedTransaction, Network}  
+//! ```no_run
+//! use elrond_rust::{Client, Account, UnsignedTransaction, Network};
 //! // initialize new client
 //! let client = Client::new();
 //! // load account from private/secret key
 //! let private_key = "a4b36a5d97176618b5a7fcc9228d2fd98ee2f14ddd3d6462ae03e40eb487d15b";
 //! let account = Account::from_string(private_key).unwrap();
 //! // look up the current account nonce to use for the tx
-//! let nonce = client.get_address_nonce(&account.address).unwrap();
+//! let nonce = client.get_address_nonce(&account.address.to_string()).unwrap();
 //! // create a new transaction to send 1 eGLD to some address
 //! let tx = UnsignedTransaction::new(
 //!     nonce, // current nonce
@@ -23,17 +23,43 @@
 //! // sign the transaction
 //! let signed_tx = tx.sign(&account).unwrap();
 //! // submit the transaction to the network
-//! client.post_signed_transaction(signed_tx)
+//! client.post_signed_transaction(signed_tx).unwrap();
 //! ```
 //!
+//! With the `async` feature enabled, `AsyncClient` offers the same methods as `Client` for use
+//! inside async runtimes. Its default transport, built on `reqwest`, needs a tokio runtime; other
+//! runtimes plug in their own `AsyncTransport`, including its timer.
+//!
 //! See the source code and tests for more examples.
 
 mod transaction;
 mod account;
 mod errors;
 mod rest;
+mod amount;
+mod signer;
+mod message;
+mod keyfile;
+mod blocks;
+pub mod offline;
+pub mod native_auth;
+pub mod relayed;
+pub mod guardian;
+pub mod esdt;
+pub mod system;
+pub mod delegation;
+pub mod dns;
+pub mod follower;
 
-pub use transaction::{ElrondCurrencyAmount, Network, UnsignedTransaction, SignedTransaction};
-pub use account::{Account, ElrondAddress};
-pub use rest::Client;
+pub use transaction::{Network, UnsignedTransaction, SignedTransaction, OPTION_HASH_SIGN, OPTION_GUARDED};
+pub use account::{Account, ElrondAddress, DEFAULT_HRP, ADDRESS_LENGTH};
+pub use signer::{Signer, RemoteSigner};
+pub use message::verify_message;
+pub use amount::{ElrondCurrencyAmount, HumanCurrencyAmount, TokenAmount};
+pub use esdt::{TokenProperties, TokenRoles, NftDetails};
+pub use blocks::{NetworkStatus, Block, BlockTransaction, Hyperblock, ShardBlockInfo, METACHAIN_SHARD};
+pub use rest::{Client, Transport, UreqTransport, MockTransport, HttpRequest, HttpResponse, Method};
+pub use rest::{RetryPolicy, RateLimiter, EndpointPool, EndpointHealth, FinalHyperblocks};
+#[cfg(feature = "async")]
+pub use rest::{AsyncClient, AsyncTransport, ReqwestTransport, TransportFuture, SleepFuture};
 pub use errors::{ElrondClientError, Result};
\ No newline at end of file
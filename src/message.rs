@@ -0,0 +1,65 @@
+//! Signing of arbitrary messages following the wallet standard, e.g., to prove ownership of an
+//! address when logging in. Messages are prefixed with "\x17Elrond Signed Message:\n" and their
+//! length, then hashed with keccak256 before being signed, so a signed message can never be
+//! mistaken for a signed transaction.
+
+use std::convert::TryFrom;
+use ed25519_dalek::{Signature, Verifier};
+use sha3::{Digest, Keccak256};
+use super::{ElrondAddress, ElrondClientError, Result};
+
+/// Prefix of messages signed with the wallet standard
+const MESSAGE_PREFIX: &str = "\x17Elrond Signed Message:\n";
+
+/// Hash of a message as signed by wallets: keccak256 of the prefix, the length and the message
+pub(crate) fn message_hash(message: &[u8]) -> Vec<u8> {
+    let mut hasher = Keccak256::new();
+    hasher.update(MESSAGE_PREFIX.as_bytes());
+    hasher.update(message.len().to_string().as_bytes());
+    hasher.update(message);
+    hasher.finalize().to_vec()
+}
+
+/// Check a hex encoded ed25519 signature of raw bytes against an address
+pub(crate) fn verify_signature(address: &ElrondAddress, data: &[u8], signature: &str) -> Result<()> {
+    let signature = hex::decode(signature)
+        .ok()
+        .and_then(|bytes| Signature::try_from(&bytes[..]).ok())
+        .ok_or_else(|| ElrondClientError::new("signature is not valid hex encoded ed25519"))?;
+    address.to_public_key()?.verify(data, &signature).map_err(|_| {
+        ElrondClientError::new(&format!("signature does not match {}", address))
+    })
+}
+
+/// Check that the hex encoded `signature` of `message` was produced by `address`, e.g., with
+/// `Account::sign_message` or a wallet
+pub fn verify_message(address: &ElrondAddress, message: &[u8], signature: &str) -> Result<()> {
+    verify_signature(address, &message_hash(message), signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::verify_message;
+    use super::super::{Account, ElrondAddress};
+
+    #[test]
+    fn erdjs_signed_message() {
+        // alice's signature of "hello", from the erdjs test suite
+        let alice = Account::from_string("413f42575f7f26fad3317a778771212fdb80245850981e48b58a4f25e344e8f9").unwrap();
+        let signature = "561bc58f1dc6b10de208b2d2c22c9a474ea5e8cabb59c3d3ce06bbda21cc46454aa71a85d5a60442bd7784effa2e062fcb8fb421c521f898abf7f5ec165e5d0f";
+        assert_eq!(alice.sign_message(b"hello").unwrap(), signature);
+        assert!(verify_message(&alice.address, b"hello", signature).is_ok());
+    }
+
+    #[test]
+    fn sign_and_verify_message() {
+        let account = Account::from_string("a4b36a5d97176618b5a7fcc9228d2fd98ee2f14ddd3d6462ae03e40eb487d15b").unwrap();
+        let signature = account.sign_message(b"hello").unwrap();
+        assert!(verify_message(&account.address, b"hello", &signature).is_ok());
+        assert!(verify_message(&account.address, b"hello!", &signature).is_err());
+        // raw signatures of the same bytes are not valid message signatures
+        assert!(verify_message(&account.address, b"hello", &account.sign("hello").unwrap()).is_err());
+        let other = ElrondAddress::new("erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k").unwrap();
+        assert!(verify_message(&other, b"hello", &signature).is_err());
+    }
+}
@@ -0,0 +1,240 @@
+//! Native auth tokens, used by backends to authenticate the owner of an address without a
+//! password. A token is `base64(address).base64(body).signature`, the body being
+//! `base64(origin).blockHash.ttl.base64(extraInfo)`, signed with the message signing standard
+//! (see `verify_message`). Tokens expire `ttl` seconds after the block they reference.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde_json::{json, Value};
+use super::message::message_hash;
+use super::{Signer, Client, Transport, ElrondAddress, verify_message, ElrondClientError, Result};
+
+/// Default lifetime of tokens, in seconds
+const DEFAULT_TTL: u64 = 86400;
+
+/// Encode a value as url-safe base64 without padding, as expected in native auth tokens
+fn encode_value(value: &str) -> String {
+    base64::encode_config(value, base64::URL_SAFE_NO_PAD)
+}
+
+/// Decode a url-safe base64 value from a native auth token, with or without padding
+fn decode_value(value: &str) -> Result<String> {
+    base64::decode_config(value.trim_end_matches('='), base64::URL_SAFE_NO_PAD)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| ElrondClientError::new(&format!("'{}' is not valid base64", value)))
+}
+
+/// Current unix time in seconds
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Decoded native auth token
+#[derive(Clone, Debug, PartialEq)]
+pub struct NativeAuthToken {
+    pub address: ElrondAddress,
+    /// Origin the token was issued for, e.g., "https://example.com"
+    pub origin: String,
+    /// Hash of a recent block of any shard or of the metachain, anchoring the token in time
+    pub block_hash: String,
+    /// Lifetime of the token in seconds, from the timestamp of the block
+    pub ttl: u64,
+    pub extra_info: Value,
+    /// Hex encoded signature of the address and body
+    pub signature: String
+}
+
+impl NativeAuthToken {
+    /// Create and sign a new token for `origin`, anchored at `block_hash`
+    pub fn new<S: Signer + ?Sized>(signer: &S, origin: &str, block_hash: &str, ttl: u64, extra_info: Value) -> Result<Self> {
+        let mut token = Self {
+            address: signer.address(),
+            origin: origin.to_string(),
+            block_hash: block_hash.to_string(),
+            ttl,
+            extra_info,
+            signature: String::new()
+        };
+        let signed = message_hash(token.signed_message().as_bytes());
+        token.signature = hex::encode(signer.sign_bytes(&signed)?);
+        Ok(token)
+    }
+
+    /// Body of the token, which is signed along with the address
+    pub fn body(&self) -> String {
+        format!(
+            "{}.{}.{}.{}",
+            encode_value(&self.origin),
+            self.block_hash,
+            self.ttl,
+            encode_value(&self.extra_info.to_string())
+        )
+    }
+
+    /// Message signed by the owner of the address
+    fn signed_message(&self) -> String {
+        format!("{}{}", self.address, self.body())
+    }
+
+    /// Encode the token to send it, e.g., in an "Authorization: Bearer" header
+    pub fn encode(&self) -> String {
+        format!(
+            "{}.{}.{}",
+            encode_value(&self.address.to_string()),
+            encode_value(&self.body()),
+            self.signature
+        )
+    }
+
+    /// Decode a token, without validating it
+    pub fn decode(token: &str) -> Result<Self> {
+        let parts: Vec<&str> = token.split('.').collect();
+        if parts.len() != 3 {
+            return Err(ElrondClientError::new("token must have 3 parts"));
+        }
+        let body = decode_value(parts[1])?;
+        let body: Vec<&str> = body.split('.').collect();
+        if body.len() != 4 {
+            return Err(ElrondClientError::new("token body must have 4 parts"));
+        }
+        let extra_info = serde_json::from_str(&decode_value(body[3])?).map_err(|_| {
+            ElrondClientError::new("token extra info is not JSON")
+        })?;
+        Ok(Self {
+            address: ElrondAddress::new(&decode_value(parts[0])?)?,
+            origin: decode_value(body[0])?,
+            block_hash: body[1].to_string(),
+            ttl: body[2].parse().map_err(|_| ElrondClientError::new("token ttl is not a number"))?,
+            extra_info,
+            signature: parts[2].to_string()
+        })
+    }
+
+    /// Check that the token was signed by its address
+    pub fn verify_signature(&self) -> Result<()> {
+        verify_message(&self.address, self.signed_message().as_bytes(), &self.signature)
+    }
+}
+
+/// Generate an encoded token for `origin`, valid for `ttl` seconds from the latest final block
+pub fn generate_token<T: Transport, S: Signer + ?Sized>(client: &Client<T>, signer: &S, origin: &str, ttl: u64) -> Result<String> {
+    let block_hash = client.get_latest_block_hash()?;
+    Ok(NativeAuthToken::new(signer, origin, &block_hash, ttl, json!({}))?.encode())
+}
+
+/// Validates incoming tokens: signature, origin, lifetime and expiry
+#[derive(Clone, Debug)]
+pub struct NativeAuthValidator {
+    accepted_origins: Vec<String>,
+    max_ttl: u64
+}
+
+impl NativeAuthValidator {
+    /// Accept tokens issued for one of `accepted_origins`, living at most one day
+    pub fn new(accepted_origins: &[&str]) -> Self {
+        Self {
+            accepted_origins: accepted_origins.iter().map(|origin| origin.to_string()).collect(),
+            max_ttl: DEFAULT_TTL
+        }
+    }
+
+    /// Reject tokens living longer than `max_ttl` seconds
+    pub fn with_max_ttl(mut self, max_ttl: u64) -> Self {
+        self.max_ttl = max_ttl;
+        self
+    }
+
+    /// Validate a token at time `now`, given the timestamp of the block it references
+    pub fn validate_at(&self, token: &str, block_timestamp: u64, now: u64) -> Result<NativeAuthToken> {
+        let token = NativeAuthToken::decode(token)?;
+        if !self.accepted_origins.contains(&token.origin) {
+            return Err(ElrondClientError::new(&format!("origin '{}' is not accepted", token.origin)));
+        }
+        if token.ttl > self.max_ttl {
+            return Err(ElrondClientError::new(&format!(
+                "token ttl {} exceeds the maximum of {}", token.ttl, self.max_ttl
+            )));
+        }
+        // a timestamp so large that the expiry overflows can only be bogus
+        let expires_at = block_timestamp
+            .checked_add(token.ttl)
+            .ok_or_else(|| ElrondClientError::new("token expiry is out of range"))?;
+        if now > expires_at {
+            return Err(ElrondClientError::new("token has expired"));
+        }
+        token.verify_signature()?;
+        Ok(token)
+    }
+
+    /// Validate a token now, looking up the timestamp of the block it references on the network
+    pub fn validate<T: Transport>(&self, client: &Client<T>, token: &str) -> Result<NativeAuthToken> {
+        let block_hash = NativeAuthToken::decode(token)?.block_hash;
+        let block_timestamp = client.get_block_timestamp(&block_hash)?;
+        self.validate_at(token, block_timestamp, now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+    use super::super::{Account, MockTransport};
+
+    fn account() -> Account {
+        Account::from_string("a4b36a5d97176618b5a7fcc9228d2fd98ee2f14ddd3d6462ae03e40eb487d15b").unwrap()
+    }
+
+    #[test]
+    fn token_round_trip() {
+        let token = NativeAuthToken::new(&account(), "https://example.com", "abcd", 600, json!({})).unwrap();
+        assert_eq!(token.body(), "aHR0cHM6Ly9leGFtcGxlLmNvbQ.abcd.600.e30");
+        let decoded = NativeAuthToken::decode(&token.encode()).unwrap();
+        assert_eq!(decoded, token);
+        assert!(decoded.verify_signature().is_ok());
+    }
+
+    #[test]
+    fn validation() {
+        let validator = NativeAuthValidator::new(&["https://example.com"]);
+        let token = NativeAuthToken::new(&account(), "https://example.com", "abcd", 600, json!({})).unwrap().encode();
+        assert!(validator.validate_at(&token, 1000, 1600).is_ok());
+        assert!(validator.validate_at(&token, 1000, 1601).is_err());
+        assert!(validator.clone().with_max_ttl(300).validate_at(&token, 1000, 1100).is_err());
+        assert!(NativeAuthValidator::new(&["https://other.com"]).validate_at(&token, 1000, 1100).is_err());
+        // the signature covers the body
+        let other = NativeAuthToken::new(&account(), "https://example.com", "ef01", 600, json!({})).unwrap();
+        let forged = format!("{}.{}", &token[..token.rfind('.').unwrap()], other.signature);
+        assert!(validator.validate_at(&forged, 1000, 1100).is_err());
+    }
+
+    #[test]
+    fn expiry_overflow_is_rejected() {
+        let validator = NativeAuthValidator::new(&["https://example.com"]).with_max_ttl(u64::MAX);
+        let token = NativeAuthToken::new(&account(), "https://example.com", "abcd", u64::MAX, json!({})).unwrap().encode();
+        assert!(validator.validate_at(&token, 1000, 1100).is_err());
+        assert!(validator.validate_at(&token, 0, 1100).is_ok());
+    }
+
+    #[test]
+    fn generate_and_validate_with_client() {
+        let client = Client::with_transport("https://api.elrond.com", MockTransport::new());
+        client.transport()
+            .push_json(&json!({"data": {"status": {"erd_highest_final_nonce": 10}}}))
+            .push_json(&json!({"data": {"hyperblock": {"hash": "abcd", "timestamp": 1}}}))
+            .push_json(&json!({"hash": "abcd", "shard": 4294967295u32, "timestamp": now()}));
+        let token = generate_token(&client, &account(), "https://example.com", 600).unwrap();
+        let validated = NativeAuthValidator::new(&["https://example.com"]).validate(&client, &token).unwrap();
+        assert_eq!(validated.address, account().address);
+        assert_eq!(validated.block_hash, "abcd");
+    }
+
+    #[test]
+    fn validate_token_anchored_on_shard_block() {
+        let client = Client::with_transport("https://api.elrond.com", MockTransport::new());
+        client.transport().push_json(&json!({"hash": "ef01", "shard": 1, "nonce": 300, "timestamp": now()}));
+        let token = NativeAuthToken::new(&account(), "https://example.com", "ef01", 600, json!({})).unwrap().encode();
+        let validated = NativeAuthValidator::new(&["https://example.com"]).validate(&client, &token).unwrap();
+        assert_eq!(validated.block_hash, "ef01");
+        assert!(client.transport().requests()[0].url.ends_with("/blocks/ef01"));
+    }
+}
@@ -0,0 +1,318 @@
+//! Offline (air-gapped) signing workflow. An unsigned transaction is prepared and exported on an
+//! online machine, carried to an offline machine holding the secret key to be signed, and the
+//! signed transaction is carried back to be broadcast:
+//!
+//! 1. online: `export_unsigned`
+//! 2. offline: `import_unsigned`, `sign_offline`, `export_signed`
+//! 3. online: `import_signed`, `broadcast`
+//!
+//! Every step validates the transaction against the same `TransactionChecks`, and hands its
+//! human-readable summary (see `UnsignedTransaction::summary`) to the review function of the
+//! checks, if any, which can display it and reject the transaction.
+//!
+//! Transactions are exported either as pretty JSON, or in a compact encoding meant for QR codes:
+//! the JSON payload encoded as uppercase bech32, which fits the alphanumeric QR mode and carries
+//! a checksum against transcription errors. Transactions are far longer than the 90 characters
+//! BIP173 limits bech32 strings to, so the checksum still catches most typos but no longer
+//! guarantees catching up to 4 errors, and other bech32 tools may refuse to decode them. Use JSON
+//! when the transaction is not scanned from a QR code.
+
+use bech32::{FromBase32, ToBase32};
+use serde::{Serialize, de::DeserializeOwned};
+use super::{Signer, Client, Transport, Network, UnsignedTransaction, SignedTransaction};
+use super::{ElrondCurrencyAmount, ElrondClientError, Result};
+
+/// bech32 prefix of unsigned transactions in the compact encoding
+const UNSIGNED_HRP: &str = "erdtx";
+/// bech32 prefix of signed transactions in the compact encoding
+const SIGNED_HRP: &str = "erdsigned";
+
+/// How transactions are encoded when moved between machines
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    /// Pretty printed JSON, e.g., for a file on a USB drive
+    Json,
+    /// Uppercase bech32, e.g., for a QR code. The payload exceeds the bech32 length limit, see
+    /// the module documentation.
+    Compact
+}
+
+/// Expectations a transaction is validated against at every step of the workflow
+#[derive(Clone, Debug)]
+pub struct TransactionChecks {
+    chain_id: String,
+    nonce: Option<u64>,
+    max_fee: ElrondCurrencyAmount,
+    review: Option<fn(&str) -> Result<()>>
+}
+
+impl TransactionChecks {
+    /// Expect transactions for `network`, costing at most 0.01 eGLD in fees
+    pub fn new(network: Network) -> Self {
+        Self {
+            chain_id: network.chain_id(),
+            nonce: None,
+            max_fee: ElrondCurrencyAmount::new("0.01").expect("valid default fee"),
+            review: None
+        }
+    }
+
+    /// Expect transactions to be executed at `nonce`
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// Reject transactions whose fee may exceed `max_fee`
+    pub fn with_max_fee(mut self, max_fee: ElrondCurrencyAmount) -> Self {
+        self.max_fee = max_fee;
+        self
+    }
+
+    /// Show the summary of the transaction to `review` at every step once it passed the checks,
+    /// e.g., to print it and ask for confirmation. An error returned by `review` aborts the step.
+    pub fn with_review(mut self, review: fn(&str) -> Result<()>) -> Self {
+        self.review = Some(review);
+        self
+    }
+
+    /// Check that a transaction is well-formed and meets the expectations, then have its summary
+    /// reviewed
+    pub fn check(&self, tx: &UnsignedTransaction) -> Result<()> {
+        tx.validate()?;
+        if tx.chain_id() != self.chain_id {
+            return Err(ElrondClientError::new(&format!(
+                "transaction is for chain '{}', expected '{}'", tx.chain_id(), self.chain_id
+            )));
+        }
+        if let Some(nonce) = self.nonce {
+            if tx.nonce() != nonce {
+                return Err(ElrondClientError::new(&format!(
+                    "transaction has nonce {}, expected {}", tx.nonce(), nonce
+                )));
+            }
+        }
+        let fee = tx.max_fee()?;
+        if fee > self.max_fee {
+            return Err(ElrondClientError::new(&format!(
+                "transaction fee may reach {} eGLD, more than the allowed {} eGLD",
+                fee.to_human(), self.max_fee.to_human()
+            )));
+        }
+        match self.review {
+            Some(review) => review(&tx.summary()?),
+            None => Ok(())
+        }
+    }
+}
+
+/// Internal helper for encoding a transaction
+fn encode<S: Serialize>(tx: &S, hrp: &str, encoding: Encoding) -> Result<String> {
+    match encoding {
+        Encoding::Json => serde_json::to_string_pretty(tx).map_err(|_| {
+            ElrondClientError::new("could not serialize transaction")
+        }),
+        Encoding::Compact => {
+            let json = serde_json::to_vec(tx).map_err(|_| {
+                ElrondClientError::new("could not serialize transaction")
+            })?;
+            bech32::encode(hrp, json.to_base32())
+                .map(|encoded| encoded.to_uppercase())
+                .map_err(|_| ElrondClientError::new("could not encode transaction as bech32"))
+        }
+    }
+}
+
+/// Internal helper for decoding a transaction in either encoding, detected from its content
+fn decode<D: DeserializeOwned>(encoded: &str, hrp: &str) -> Result<D> {
+    let encoded = encoded.trim();
+    let json = if encoded.starts_with('{') {
+        encoded.as_bytes().to_vec()
+    } else {
+        let (found_hrp, data) = bech32::decode(encoded).map_err(|_| {
+            ElrondClientError::new("transaction is neither JSON nor valid bech32")
+        })?;
+        if found_hrp != hrp {
+            return Err(ElrondClientError::new(&format!(
+                "expected a '{}' transaction, found '{}'", hrp, found_hrp
+            )));
+        }
+        Vec::<u8>::from_base32(&data).map_err(|_| {
+            ElrondClientError::new("could not decode bech32 transaction")
+        })?
+    };
+    serde_json::from_slice(&json).map_err(|_| {
+        ElrondClientError::new("could not deserialize transaction")
+    })
+}
+
+/// Step 1 (online): validate an unsigned transaction and export it for the offline machine
+pub fn export_unsigned(tx: &UnsignedTransaction, checks: &TransactionChecks, encoding: Encoding) -> Result<String> {
+    checks.check(tx)?;
+    encode(tx, UNSIGNED_HRP, encoding)
+}
+
+/// Step 2 (offline): import and validate an unsigned transaction
+pub fn import_unsigned(encoded: &str, checks: &TransactionChecks) -> Result<UnsignedTransaction> {
+    let tx: UnsignedTransaction = decode(encoded, UNSIGNED_HRP)?;
+    checks.check(&tx)?;
+    Ok(tx)
+}
+
+/// Step 2 (offline): validate and sign an unsigned transaction, which must be sent by the address
+/// of `signer`
+pub fn sign_offline<S: Signer + ?Sized>(tx: &UnsignedTransaction, signer: &S, checks: &TransactionChecks) -> Result<SignedTransaction> {
+    checks.check(tx)?;
+    if tx.sender() != signer.address().to_string() {
+        return Err(ElrondClientError::new("transaction is not sent by the signing account"));
+    }
+    tx.sign(signer)
+}
+
+/// Step 2 (offline): export a signed transaction for the online machine
+pub fn export_signed(tx: &SignedTransaction, encoding: Encoding) -> Result<String> {
+    encode(tx, SIGNED_HRP, encoding)
+}
+
+/// Step 3 (online): import a signed transaction, validating it and its signature
+pub fn import_signed(encoded: &str, checks: &TransactionChecks) -> Result<SignedTransaction> {
+    let tx: SignedTransaction = decode(encoded, SIGNED_HRP)?;
+    checks.check(&tx.unsigned())?;
+    tx.verify()?;
+    Ok(tx)
+}
+
+/// Step 3 (online): broadcast a signed transaction and return its hash, after checking that its
+/// nonce is the current nonce of the sender
+pub fn broadcast<T: Transport>(client: &Client<T>, tx: SignedTransaction, checks: &TransactionChecks) -> Result<String> {
+    let unsigned = tx.unsigned();
+    checks.check(&unsigned)?;
+    let nonce = client.get_address_nonce(unsigned.sender())?;
+    if unsigned.nonce() != nonce {
+        return Err(ElrondClientError::new(&format!(
+            "transaction has nonce {} but the sender is at nonce {}", unsigned.nonce(), nonce
+        )));
+    }
+    client.post_signed_transaction(tx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use super::super::{Account, MockTransport};
+
+    fn account() -> Account {
+        Account::from_string("a4b36a5d97176618b5a7fcc9228d2fd98ee2f14ddd3d6462ae03e40eb487d15b").unwrap()
+    }
+
+    fn unsigned(nonce: u64) -> UnsignedTransaction {
+        UnsignedTransaction::new(
+            nonce,
+            "0.001",
+            "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k",
+            &account().address.to_string(),
+            Network::MainNet
+        ).unwrap()
+    }
+
+    #[test]
+    fn round_trip_in_both_encodings() {
+        let checks = TransactionChecks::new(Network::MainNet).with_nonce(5);
+        for &encoding in &[Encoding::Json, Encoding::Compact] {
+            let exported = export_unsigned(&unsigned(5), &checks, encoding).unwrap();
+            let tx = import_unsigned(&exported, &checks).unwrap();
+            let signed = sign_offline(&tx, &account(), &checks).unwrap();
+            let exported = export_signed(&signed, encoding).unwrap();
+            let imported = import_signed(&exported, &checks).unwrap();
+            assert_eq!(imported.serialize().unwrap(), signed.serialize().unwrap());
+        }
+    }
+
+    #[test]
+    fn compact_encoding_is_qr_alphanumeric() {
+        let checks = TransactionChecks::new(Network::MainNet);
+        let exported = export_unsigned(&unsigned(0), &checks, Encoding::Compact).unwrap();
+        assert!(exported.starts_with("ERDTX1"));
+        assert!(exported.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()));
+        // signed transactions can not be mistaken for unsigned ones
+        let signed = export_signed(&unsigned(0).sign(&account()).unwrap(), Encoding::Compact).unwrap();
+        assert!(import_unsigned(&signed, &checks).is_err());
+        // a single typo is caught by the checksum
+        let mut typo = exported.into_bytes();
+        typo[20] = if typo[20] == b'Q' { b'P' } else { b'Q' };
+        assert!(import_unsigned(&String::from_utf8(typo).unwrap(), &checks).is_err());
+    }
+
+    #[test]
+    fn checks_reject_unexpected_transactions() {
+        let tx = unsigned(5);
+        assert!(TransactionChecks::new(Network::Custom("D".to_string())).check(&tx).is_err());
+        assert!(TransactionChecks::new(Network::MainNet).with_nonce(6).check(&tx).is_err());
+        let low_fee = ElrondCurrencyAmount::new("0.00001").unwrap();
+        assert!(TransactionChecks::new(Network::MainNet).with_max_fee(low_fee).check(&tx).is_err());
+        assert!(TransactionChecks::new(Network::MainNet).with_nonce(5).check(&tx).is_ok());
+    }
+
+    #[test]
+    fn corrupted_unsigned_transaction_is_rejected() {
+        let checks = TransactionChecks::new(Network::MainNet);
+        let exported = export_unsigned(&unsigned(0), &checks, Encoding::Json).unwrap();
+        assert!(import_unsigned(&exported, &checks).is_ok());
+        let corrupted = [
+            exported.replace("erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k", "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65q"),
+            exported.replace("\"value\": \"1000000000000000\"", "\"value\": \"-1000000000000000\""),
+            exported.replace("\"value\": \"1000000000000000\"", "\"value\": \"1e15\""),
+            exported.replace("\"sender\": \"erd1", "\"sender\": \"btc1")
+        ];
+        for corrupted in &corrupted {
+            assert_ne!(corrupted, &exported);
+            assert!(import_unsigned(corrupted, &checks).is_err());
+        }
+    }
+
+    #[test]
+    fn review_sees_summary_at_every_step() {
+        fn reject_large(summary: &str) -> Result<()> {
+            if summary.starts_with("Send 0.001 eGLD") {
+                Ok(())
+            } else {
+                Err(ElrondClientError::new("rejected by the operator"))
+            }
+        }
+        let checks = TransactionChecks::new(Network::MainNet).with_review(reject_large);
+        let exported = export_unsigned(&unsigned(0), &checks, Encoding::Json).unwrap();
+        assert!(sign_offline(&import_unsigned(&exported, &checks).unwrap(), &account(), &checks).is_ok());
+        let large = UnsignedTransaction::new(0, "1", &account().address.to_string(), &account().address.to_string(), Network::MainNet).unwrap();
+        assert!(export_unsigned(&large, &checks, Encoding::Json).is_err());
+    }
+
+    #[test]
+    fn tampered_signed_transaction_is_rejected() {
+        let checks = TransactionChecks::new(Network::MainNet);
+        let signed = unsigned(0).sign(&account()).unwrap();
+        let exported = export_signed(&signed, Encoding::Json).unwrap();
+        let tampered = exported.replace("1000000000000000", "9000000000000000");
+        assert!(import_signed(&tampered, &checks).is_err());
+    }
+
+    #[test]
+    fn summary_is_human_readable() {
+        let summary = unsigned(3).summary().unwrap();
+        assert!(summary.starts_with("Send 0.001 eGLD"));
+        assert!(summary.contains("nonce:    3"));
+        assert!(summary.contains("max fee:  0.00005 eGLD (50000 gas at 1000000000)"));
+    }
+
+    #[test]
+    fn broadcast_checks_current_nonce() {
+        let checks = TransactionChecks::new(Network::MainNet);
+        let client = Client::with_transport("https://api.elrond.com", MockTransport::new());
+        client.transport().push_json(&json!({"data": {"nonce": 4}, "code": "successful"}));
+        assert!(broadcast(&client, unsigned(3).sign(&account()).unwrap(), &checks).is_err());
+        client.transport().push_json(&json!({"data": {"nonce": 3}, "code": "successful"}));
+        client.transport().push_json(&json!({"data": {"txHash": "abc"}, "code": "successful"}));
+        let hash = broadcast(&client, unsigned(3).sign(&account()).unwrap(), &checks).unwrap();
+        assert_eq!(hash, "abc");
+    }
+}
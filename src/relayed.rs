@@ -0,0 +1,233 @@
+//! Relayed transactions, where a relayer pays the gas of a transaction signed by another account.
+//!
+//! - v1 (`relayed_v1`) wraps the JSON of the signed inner transaction in `relayedTx@<hex>`
+//! - v2 (`relayed_v2`) wraps its fields in `relayedTxV2@<receiver>@<nonce>@<data>@<signature>`,
+//!   the inner transaction being signed with a gas limit of 0
+//! - v3 needs no wrapping: see `UnsignedTransaction::with_relayer` and
+//!   `SignedTransaction::sign_as_relayer`
+//!
+//! The relayer transaction is sent from the relayer to the sender of the inner transaction, its
+//! gas limit covering both its own data and the gas of the inner transaction.
+
+use super::system::u64_arg;
+use super::transaction::{MIN_GAS_LIMIT, GAS_PER_DATA_BYTE};
+use super::{Network, UnsignedTransaction, SignedTransaction, ElrondAddress, ElrondClientError, Result};
+
+/// Internal helper for building the relayer transaction carrying `data`
+fn relayer_transaction(inner: &UnsignedTransaction, relayer: &str, relayer_nonce: u64, data: &[u8], inner_gas_limit: u64) -> Result<UnsignedTransaction> {
+    let gas_limit = MIN_GAS_LIMIT + GAS_PER_DATA_BYTE * data.len() as u64 + inner_gas_limit;
+    Ok(UnsignedTransaction::new(
+        relayer_nonce,
+        "0",
+        inner.sender(),
+        relayer,
+        Network::Custom(inner.chain_id().to_string())
+    )?
+    .with_data(data)
+    .with_gas_limit(gas_limit)
+    .with_gas_price(inner.gas_price()))
+}
+
+/// Public key bytes of a bech32 address
+fn address_bytes(address: &str) -> Result<Vec<u8>> {
+    Ok(ElrondAddress::new(address)?.to_bytes().to_vec())
+}
+
+/// JSON of the inner transaction of a relayed v1 transaction, as built by the reference SDKs.
+/// Addresses, data, signatures, chain id and usernames are base64 encoded, and the value is a bare
+/// number.
+fn inner_json(inner: &SignedTransaction) -> Result<String> {
+    let tx = inner.unsigned();
+    let signature_base64 = |signature: &str| -> Result<String> {
+        hex::decode(signature).map(base64::encode).map_err(|_| {
+            ElrondClientError::new("signature is not valid hex")
+        })
+    };
+    let mut json = format!(
+        "{{\"nonce\":{},\"sender\":\"{}\",\"receiver\":\"{}\",\"value\":{},\"gasPrice\":{},\"gasLimit\":{},\"data\":\"{}\",\"signature\":\"{}\",\"chainID\":\"{}\",\"version\":{}",
+        tx.nonce(),
+        base64::encode(address_bytes(tx.sender())?),
+        base64::encode(address_bytes(tx.receiver())?),
+        tx.value()?,
+        tx.gas_price(),
+        tx.gas_limit(),
+        base64::encode(tx.data()?),
+        signature_base64(inner.signature())?,
+        base64::encode(tx.chain_id()),
+        tx.version()
+    );
+    if tx.options() != 0 {
+        json += &format!(",\"options\":{}", tx.options());
+    }
+    match (tx.guardian(), inner.guardian_signature()) {
+        (Some(guardian), Some(signature)) => {
+            json += &format!(
+                ",\"guardian\":\"{}\",\"guardianSignature\":\"{}\"",
+                base64::encode(address_bytes(guardian)?),
+                signature_base64(signature)?
+            );
+        },
+        (None, None) => {},
+        // the guarded option would be kept without the guardian, and rejected by the network
+        _ => return Err(ElrondClientError::new("inner transaction has a guardian without its signature, or the reverse"))
+    }
+    let sender_username = tx.sender_username()?;
+    if !sender_username.is_empty() {
+        json += &format!(",\"sndUserName\":\"{}\"", base64::encode(sender_username));
+    }
+    let receiver_username = tx.receiver_username()?;
+    if !receiver_username.is_empty() {
+        json += &format!(",\"rcvUserName\":\"{}\"", base64::encode(receiver_username));
+    }
+    Ok(json + "}")
+}
+
+/// Build a relayed v1 transaction from `relayer`, paying the gas of `inner`, whose signatures
+/// must be valid
+pub fn relayed_v1(inner: &SignedTransaction, relayer: &str, relayer_nonce: u64) -> Result<UnsignedTransaction> {
+    inner.verify()?;
+    let data = format!("relayedTx@{}", hex::encode(inner_json(inner)?));
+    let tx = inner.unsigned();
+    relayer_transaction(&tx, relayer, relayer_nonce, data.as_bytes(), tx.gas_limit())
+}
+
+/// Build a relayed v2 transaction from `relayer`, paying the gas of `inner`. The inner transaction
+/// must have been signed with a gas limit of 0, the gas it needs being given as `inner_gas_limit`.
+/// It can not transfer eGLD, and its signature must be valid.
+pub fn relayed_v2(inner: &SignedTransaction, inner_gas_limit: u64, relayer: &str, relayer_nonce: u64) -> Result<UnsignedTransaction> {
+    inner.verify()?;
+    let tx = inner.unsigned();
+    if tx.gas_limit() != 0 {
+        return Err(ElrondClientError::new("inner transaction of relayed v2 must have a gas limit of 0"));
+    }
+    if !tx.value()?.to_token_amount().is_zero() {
+        return Err(ElrondClientError::new("inner transaction of relayed v2 can not transfer eGLD"));
+    }
+    let data = format!(
+        "relayedTxV2@{}@{}@{}@{}",
+        hex::encode(address_bytes(tx.receiver())?),
+        hex::encode(u64_arg(tx.nonce())),
+        hex::encode(tx.data()?),
+        inner.signature()
+    );
+    relayer_transaction(&tx, relayer, relayer_nonce, data.as_bytes(), inner_gas_limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{relayed_v1, relayed_v2, inner_json};
+    use super::super::{Account, ElrondAddress, UnsignedTransaction, SignedTransaction, Network};
+
+    const RELAYER: &str = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+    const RECEIVER: &str = "erd1qyu5wthldzr8wx5c9ucg8kjagg0jfs53s8nr3zpz3hypefsdd8ssycr6th";
+
+    fn user() -> Account {
+        Account::from_string("a4b36a5d97176618b5a7fcc9228d2fd98ee2f14ddd3d6462ae03e40eb487d15b").unwrap()
+    }
+
+    fn inner(value: &str) -> UnsignedTransaction {
+        UnsignedTransaction::new(
+            3,
+            value,
+            RECEIVER,
+            &user().address.to_string(),
+            Network::Custom("D".to_string())
+        ).unwrap()
+    }
+
+    #[test]
+    fn relayed_v1_wraps_inner_json() {
+        let signed = inner("1").with_data(b"add@05").with_gas_limit(5000000).sign(&user()).unwrap();
+        let tx = relayed_v1(&signed, RELAYER, 9).unwrap();
+        let data = String::from_utf8(tx.data().unwrap()).unwrap();
+        let json = String::from_utf8(hex::decode(&data["relayedTx@".len()..]).unwrap()).unwrap();
+        assert!(json.starts_with("{\"nonce\":3,\"sender\":\""));
+        assert!(json.contains("\"value\":1000000000000000000,"));
+        assert!(json.contains("\"data\":\"YWRkQDA1\""));
+        assert!(json.contains("\"chainID\":\"RA==\",\"version\":1}"));
+        assert_eq!(tx.receiver(), user().address.to_string());
+        assert_eq!(tx.sender(), RELAYER);
+        assert_eq!(tx.nonce(), 9);
+        assert_eq!(tx.gas_limit(), 50000 + 1500 * data.len() as u64 + 5000000);
+        assert_eq!(tx.chain_id(), "D");
+    }
+
+    #[test]
+    fn relayed_v1_keeps_usernames() {
+        let signed = inner("1")
+            .with_sender_username("alice.elrond")
+            .with_receiver_username("bob.elrond")
+            .sign(&user())
+            .unwrap();
+        let tx = relayed_v1(&signed, RELAYER, 9).unwrap();
+        let data = String::from_utf8(tx.data().unwrap()).unwrap();
+        let json = String::from_utf8(hex::decode(&data["relayedTx@".len()..]).unwrap()).unwrap();
+        assert!(json.ends_with("\"version\":1,\"sndUserName\":\"YWxpY2UuZWxyb25k\",\"rcvUserName\":\"Ym9iLmVscm9uZA==\"}"));
+    }
+
+    #[test]
+    fn relayed_v1_keeps_guardian() {
+        let guardian = Account::generate().unwrap();
+        let guarded = inner("1").with_guardian(&guardian.address.to_string()).unwrap().sign(&user()).unwrap();
+        // without its signature the guardian would be dropped, leaving the guarded option
+        assert!(inner_json(&guarded).is_err());
+        assert!(relayed_v1(&guarded, RELAYER, 9).is_err());
+        let guarded = guarded.sign_as_guardian(&guardian).unwrap();
+        let tx = relayed_v1(&guarded, RELAYER, 9).unwrap();
+        let data = String::from_utf8(tx.data().unwrap()).unwrap();
+        let json = String::from_utf8(hex::decode(&data["relayedTx@".len()..]).unwrap()).unwrap();
+        assert!(json.contains(&format!("\"guardian\":\"{}\"", base64::encode(guardian.address.to_bytes()))));
+    }
+
+    #[test]
+    fn relayers_reject_invalid_inner_transactions() {
+        let signed = inner("0").with_gas_limit(0).sign(&user()).unwrap();
+        let mut json: serde_json::Value = serde_json::from_str(&signed.serialize().unwrap()).unwrap();
+        json["nonce"] = 4.into();
+        let tampered: SignedTransaction = serde_json::from_value(json).unwrap();
+        assert!(relayed_v1(&tampered, RELAYER, 9).is_err());
+        assert!(relayed_v2(&tampered, 5000000, RELAYER, 9).is_err());
+        assert!(relayed_v2(&signed, 5000000, RELAYER, 9).is_ok());
+    }
+
+    #[test]
+    fn relayed_v2_requires_zero_gas() {
+        let signed = inner("0").with_data(b"add@05").with_gas_limit(0).sign(&user()).unwrap();
+        let tx = relayed_v2(&signed, 5000000, RELAYER, 9).unwrap();
+        let data = String::from_utf8(tx.data().unwrap()).unwrap();
+        assert_eq!(
+            data,
+            format!(
+                "relayedTxV2@{}@03@616464403035@{}",
+                ElrondAddress::new(RECEIVER).unwrap().to_hex(),
+                signed.signature()
+            )
+        );
+        assert_eq!(tx.gas_limit(), 50000 + 1500 * data.len() as u64 + 5000000);
+        let with_gas = inner("0").with_gas_limit(100).sign(&user()).unwrap();
+        assert!(relayed_v2(&with_gas, 5000000, RELAYER, 9).is_err());
+    }
+
+    #[test]
+    fn relayed_v3_is_signed_by_both() {
+        let relayer = Account::generate().unwrap();
+        let tx = inner("1").with_relayer(&relayer.address.to_string()).unwrap();
+        assert_eq!(tx.gas_limit(), 100000);
+        let serialized = tx.serialize().unwrap();
+        assert!(serialized.ends_with(&format!("\"version\":1,\"relayer\":\"{}\"}}", relayer.address)));
+        let signed = tx.sign(&user()).unwrap();
+        assert!(signed.clone().sign_as_relayer(&user()).is_err());
+        // relayed transactions are only valid once signed by the relayer
+        assert!(signed.verify().is_err());
+        let signed = signed.sign_as_relayer(&relayer).unwrap();
+        assert!(signed.verify().is_ok());
+        assert!(signed.serialize().unwrap().contains("\"relayerSignature\":"));
+        // a relayer signature without a relayer is rejected as well
+        let unrelayed = inner("1").sign(&user()).unwrap();
+        let mut json: serde_json::Value = serde_json::from_str(&unrelayed.serialize().unwrap()).unwrap();
+        json["relayerSignature"] = signed.relayer_signature().unwrap().into();
+        let tampered: SignedTransaction = serde_json::from_value(json).unwrap();
+        assert!(unrelayed.verify().is_ok());
+        assert!(tampered.verify().is_err());
+    }
+}
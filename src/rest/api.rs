@@ -0,0 +1,698 @@
+//! Request construction, dispatching and response parsing shared by the blocking and async
+//! clients. Each API method is described by an `ApiCall`, and a `Dispatcher` decides where each
+//! attempt goes and how long to wait, so the clients only have to send requests and sleep.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::time::Duration;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use num_bigint::BigUint;
+use super::transport::{Method, HttpRequest, HttpResponse};
+use super::retry::{self, RetryPolicy, RateLimiter};
+use super::endpoints::{self, EndpointPool};
+use super::super::{SignedTransaction, ElrondCurrencyAmount, ElrondAddress, TokenAmount, Result, ElrondClientError};
+use super::super::esdt::{self, TokenProperties, TokenRoles, NftDetails};
+use super::super::dns;
+use super::super::blocks::{NetworkStatus, Block, Hyperblock, METACHAIN_SHARD};
+
+/// Outgoing request, relative to the endpoint of a client
+#[derive(Clone, Debug)]
+pub(crate) struct ApiRequest {
+    pub method: Method,
+    pub path: String,
+    pub data: Option<Value>,
+    // whether sending the request more than once has the same effect as sending it once
+    pub replay_safe: bool
+}
+
+impl ApiRequest {
+    /// GET request, always safe to replay
+    pub fn get(path: String) -> Self {
+        Self { method: Method::Get, path, data: None, replay_safe: true }
+    }
+    /// POST request with a JSON body, not replayed unless marked with `replay_safe`
+    pub fn post(path: String, data: Value) -> Self {
+        Self { method: Method::Post, path, data: Some(data), replay_safe: false }
+    }
+    /// Mark a request as safe to replay
+    pub fn replay_safe(mut self) -> Self {
+        self.replay_safe = true;
+        self
+    }
+    /// Build the HTTP request to send to `endpoint`
+    pub fn to_http(&self, endpoint: &str) -> HttpRequest {
+        HttpRequest {
+            method: self.method,
+            url: format!("{}/{}", endpoint, self.path),
+            body: self.data.clone()
+        }
+    }
+}
+
+/// Function turning the JSON response to a request into a result
+pub(crate) type Parser<T> = Box<dyn Fn(&Value) -> Result<T> + Send + Sync>;
+
+/// A request paired with the function that parses its response
+pub(crate) struct ApiCall<T> {
+    pub request: ApiRequest,
+    pub parse: Parser<T>
+}
+
+impl<T> ApiCall<T> {
+    pub fn new<F>(request: ApiRequest, parse: F) -> Self
+    where
+        F: Fn(&Value) -> Result<T> + Send + Sync + 'static
+    {
+        Self { request, parse: Box::new(parse) }
+    }
+}
+
+/// Endpoints, retry policy and rate limiter of a client. Both clients drive the same dispatcher,
+/// each with their own way of sending requests and waiting.
+pub(crate) struct Dispatcher {
+    pub endpoints: EndpointPool,
+    pub retry_policy: RetryPolicy,
+    pub rate_limiter: Option<RateLimiter>
+}
+
+impl Dispatcher {
+    /// Dispatch requests over `endpoints`, without retries or rate limiting
+    pub fn new(endpoints: EndpointPool) -> Self {
+        Self { endpoints, retry_policy: RetryPolicy::none(), rate_limiter: None }
+    }
+    /// Start dispatching the attempts of a request
+    pub fn attempts(&self, request: &ApiRequest) -> Attempts<'_> {
+        Attempts { dispatcher: self, replay_safe: request.replay_safe, attempt: 1, tried: Vec::new() }
+    }
+    /// How long to wait before sending a request to respect the rate limit, if any
+    pub fn pace(&self) -> Option<Duration> {
+        self.rate_limiter.as_ref().map(|rate_limiter| rate_limiter.reserve())
+    }
+    /// HTTP request sending `request` to endpoint `index`
+    pub fn to_http(&self, index: usize, request: &ApiRequest) -> HttpRequest {
+        request.to_http(&self.endpoints.url(index))
+    }
+    /// Record the outcome of a request sent to endpoint `index` in the health of the endpoint
+    pub fn record(&self, index: usize, outcome: &Result<HttpResponse>) {
+        self.endpoints.record(index, retry::is_failure(outcome));
+    }
+    /// Result of a call sent to several endpoints, given the outcome from each of them. Endpoints
+    /// that failed are skipped, and those that answered must agree.
+    pub fn agreed_result<T: PartialEq + Debug>(&self, call: &ApiCall<T>, outcomes: Vec<(usize, Result<HttpResponse>)>) -> Result<T> {
+        let mut results = Vec::new();
+        let mut last_error = None;
+        for (index, outcome) in outcomes {
+            match handle_outcome(outcome).and_then(|value| (call.parse)(&value)) {
+                Ok(result) => results.push((self.endpoints.url(index), result)),
+                Err(e) => last_error = Some(e)
+            }
+        }
+        endpoints::agreed_result(results, last_error)
+    }
+}
+
+/// What a client does after an attempt of a request
+#[derive(Debug, PartialEq)]
+pub(crate) enum Step {
+    /// Send the request again right away, to the next endpoint
+    Failover,
+    /// Send the request again after waiting
+    Retry(Duration),
+    /// Stop, the outcome of the last attempt is final
+    Done
+}
+
+/// Attempts of a request: which endpoint the next one goes to, and whether to make it at all.
+/// Requests that are safe to replay fail over to other healthy endpoints, and are retried
+/// according to the retry policy once every endpoint has been tried.
+pub(crate) struct Attempts<'a> {
+    dispatcher: &'a Dispatcher,
+    replay_safe: bool,
+    attempt: u32,
+    tried: Vec<usize>
+}
+
+impl<'a> Attempts<'a> {
+    /// Endpoint to send the next attempt to
+    pub fn endpoint(&self) -> usize {
+        self.dispatcher.endpoints.select(&self.tried)
+    }
+    /// Decide what to do after an attempt sent to endpoint `index`
+    pub fn next(&mut self, index: usize, outcome: &Result<HttpResponse>) -> Step {
+        if retry::is_retryable(outcome) && self.replay_safe {
+            self.tried.push(index);
+            if self.dispatcher.endpoints.has_alternative(&self.tried) {
+                return Step::Failover;
+            }
+        }
+        match self.dispatcher.retry_policy.retry_delay(self.attempt, self.replay_safe, outcome) {
+            Some(delay) => {
+                self.tried.clear();
+                self.attempt += 1;
+                Step::Retry(delay)
+            },
+            None => Step::Done
+        }
+    }
+}
+
+/// Turn the outcome of sending a request into JSON, or an error
+pub(crate) fn handle_outcome(outcome: Result<HttpResponse>) -> Result<Value> {
+    let response = outcome?;
+    handle_response(response.status, &response.body)
+}
+
+/// Turn the status code and body of an HTTP response into JSON, or an error
+pub(crate) fn handle_response(status: u16, body: &str) -> Result<Value> {
+    if (200..300).contains(&status) {
+        serde_json::from_str(body).map_err(|_| {
+            ElrondClientError::new("could not decode response to JSON")
+        })
+    } else {
+        Err(ElrondClientError::new(
+            &format!(
+                "error code {}, data='{}'",
+                status,
+                body
+            )
+        ))
+    }
+}
+
+/// Unpackage 'data' object from Elrond API response
+fn parse_response_data(response: &Value) -> Result<&Map<String, Value>> {
+    response
+        .as_object()
+        .ok_or(
+            ElrondClientError::new("response is not a JSON object")
+        )?
+        .get("data")
+        .ok_or(
+            ElrondClientError::new("response does not contain 'data' field")
+        )?
+        .as_object()
+        .ok_or(
+            ElrondClientError::new("'data' is not a JSON object")
+        )
+}
+
+/// Get a field of a JSON object
+fn get_field<'a>(object: &'a Map<String, Value>, name: &str) -> Result<&'a Value> {
+    object.get(name).ok_or_else(|| {
+        ElrondClientError::new(&format!("response does not contain '{}' field", name))
+    })
+}
+
+/// Get a string field of a JSON object
+fn get_str<'a>(object: &'a Map<String, Value>, name: &str) -> Result<&'a str> {
+    get_field(object, name)?.as_str().ok_or_else(|| {
+        ElrondClientError::new(&format!("'{}' is not a string", name))
+    })
+}
+
+/// Get a numeric field of a JSON object, which the API sometimes sends as a string
+fn get_u64(object: &Map<String, Value>, name: &str) -> Result<u64> {
+    let field = get_field(object, name)?;
+    field
+        .as_u64()
+        .or_else(|| field.as_str().and_then(|value| value.parse().ok()))
+        .ok_or_else(|| ElrondClientError::new(&format!("'{}' is not a number", name)))
+}
+
+/// Get an object field of a JSON object
+fn get_object<'a>(object: &'a Map<String, Value>, name: &str) -> Result<&'a Map<String, Value>> {
+    get_field(object, name)?.as_object().ok_or_else(|| {
+        ElrondClientError::new(&format!("'{}' is not a JSON object", name))
+    })
+}
+
+/// Decode a base64 string sent by the API
+fn decode_base64(value: &str) -> Result<Vec<u8>> {
+    base64::decode(value).map_err(|_| {
+        ElrondClientError::new(&format!("'{}' is not valid base64", value))
+    })
+}
+
+/// Query a smart contract function through the VM, without sending a transaction
+pub(crate) fn vm_query(sc_address: &ElrondAddress, func_name: &str, args: &[Vec<u8>]) -> ApiRequest {
+    let args: Vec<String> = args.iter().map(hex::encode).collect();
+    let data = json!({
+        "scAddress": sc_address.to_string(),
+        "funcName": func_name,
+        "args": args
+    });
+    // queries never change state
+    ApiRequest::post("vm-values/query".to_string(), data).replay_safe()
+}
+
+/// Decode the return data of a VM query, failing if the query itself failed
+pub(crate) fn parse_return_data(response: &Value) -> Result<Vec<Vec<u8>>> {
+    let data = get_object(parse_response_data(response)?, "data")?;
+    let return_code = data.get("returnCode").and_then(Value::as_str).unwrap_or("ok");
+    if return_code != "ok" {
+        let message = data.get("returnMessage").and_then(Value::as_str).unwrap_or("");
+        return Err(ElrondClientError::new(
+            &format!("vm query failed with '{}': '{}'", return_code, message)
+        ));
+    }
+    match data.get("returnData") {
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| match item {
+                Value::String(encoded) => decode_base64(encoded),
+                // empty results are sometimes sent as null
+                _ => Ok(Vec::new())
+            })
+            .collect(),
+        _ => Ok(Vec::new())
+    }
+}
+
+/// Cheap request used to check whether an endpoint is up
+pub(crate) fn health_check() -> ApiRequest {
+    ApiRequest::get("network/config".to_string())
+}
+
+/// Get the current nonce associated with an address
+pub(crate) fn address_nonce(addr_str: &str) -> Result<ApiCall<u64>> {
+    let address = ElrondAddress::new(addr_str)?;
+    Ok(ApiCall::new(
+        ApiRequest::get(format!("address/{}/nonce", address)),
+        |response| {
+            parse_response_data(response)?
+                .get("nonce")
+                .ok_or(
+                    ElrondClientError::new("response does not contain 'nonce' field")
+                )?
+                .as_u64()
+                .ok_or(
+                    ElrondClientError::new("'nonce' is not a number")
+                )
+        }
+    ))
+}
+
+/// Post signed transaction to Elrond network, returning the hash of the transaction
+pub(crate) fn send_transaction(signed_tx: &SignedTransaction) -> Result<ApiCall<String>> {
+    let serialized_tx = signed_tx.serialize()?;
+    // this unwrap is safe, just serialized it...
+    let json_tx = serde_json::from_str(&serialized_tx).unwrap();
+    Ok(ApiCall::new(
+        // a signed transaction is bound to its nonce and can only execute once, so
+        // broadcasting it again is harmless
+        ApiRequest::post("transaction/send".to_string(), json_tx).replay_safe(),
+        |response| {
+            let tx_hash = parse_response_data(response)?
+                .get("txHash")
+                .ok_or(ElrondClientError::new("response does not contain 'txHash' field"))?
+                .as_str()
+                .ok_or(ElrondClientError::new("tx hash is not a string"))?;
+            Ok(tx_hash.to_string())
+        }
+    ))
+}
+
+/// Get the status of a transaction, e.g., "pending", "success" or "fail"
+pub(crate) fn transaction_status(tx_hash: &str) -> ApiCall<String> {
+    ApiCall::new(
+        ApiRequest::get(format!("transaction/{}/status", tx_hash)),
+        |response| Ok(get_str(parse_response_data(response)?, "status")?.to_string())
+    )
+}
+
+/// Get the balance associated with an Elrond address
+pub(crate) fn address_balance(addr_str: &str) -> Result<ApiCall<ElrondCurrencyAmount>> {
+    let address = ElrondAddress::new(addr_str)?;
+    Ok(ApiCall::new(
+        ApiRequest::get(format!("address/{}/balance", address)),
+        |response| {
+            let balance = parse_response_data(response)?
+                .get("balance")
+                .ok_or(
+                    ElrondClientError::new("response does not contain 'balance' field")
+                )?
+                .as_str()
+                .ok_or(
+                    ElrondClientError::new("'balance' is not a number")
+                )?;
+            ElrondCurrencyAmount::from_denominated(balance)
+        }
+    ))
+}
+
+/// ESDT token held by an address, before the decimals of its collection are known
+pub(crate) struct EsdtEntry {
+    identifier: String,
+    balance: String
+}
+
+/// Get every ESDT token (fungible or not) held by an address
+pub(crate) fn esdt_entries(addr_str: &str) -> Result<ApiCall<Vec<EsdtEntry>>> {
+    let address = ElrondAddress::new(addr_str)?;
+    Ok(ApiCall::new(
+        ApiRequest::get(format!("address/{}/esdt", address)),
+        |response| {
+            get_object(parse_response_data(response)?, "esdts")?
+                .iter()
+                .map(|(identifier, token)| {
+                    let token = token.as_object().ok_or_else(|| {
+                        ElrondClientError::new("ESDT token is not a JSON object")
+                    })?;
+                    Ok(EsdtEntry {
+                        identifier: identifier.clone(),
+                        balance: get_str(token, "balance")?.to_string()
+                    })
+                })
+                .collect()
+        }
+    ))
+}
+
+/// Collections of the given ESDT tokens, without duplicates
+pub(crate) fn esdt_collections(entries: &[EsdtEntry]) -> Vec<String> {
+    let mut collections: Vec<String> = entries
+        .iter()
+        .map(|entry| esdt::collection_of(&entry.identifier))
+        .collect();
+    collections.sort();
+    collections.dedup();
+    collections
+}
+
+/// Turn ESDT tokens into amounts, given the decimals of each collection
+pub(crate) fn esdt_amounts(entries: Vec<EsdtEntry>, decimals: &HashMap<String, u32>) -> Result<Vec<TokenAmount>> {
+    entries
+        .into_iter()
+        .map(|entry| {
+            let token_decimals = decimals
+                .get(&esdt::collection_of(&entry.identifier))
+                .copied()
+                .unwrap_or(0);
+            TokenAmount::from_base_units(&entry.balance, token_decimals, &entry.identifier)
+        })
+        .collect()
+}
+
+/// Get the balance of a single ESDT token held by an address
+pub(crate) fn esdt_balance(addr_str: &str, identifier: &str, decimals: u32) -> Result<ApiCall<TokenAmount>> {
+    let address = ElrondAddress::new(addr_str)?;
+    let token = identifier.to_string();
+    Ok(ApiCall::new(
+        ApiRequest::get(format!("address/{}/esdt/{}", address, identifier)),
+        move |response| {
+            let token_data = get_object(parse_response_data(response)?, "tokenData")?;
+            TokenAmount::from_base_units(get_str(token_data, "balance")?, decimals, &token)
+        }
+    ))
+}
+
+/// Get the details of an NFT, SFT or MetaESDT held by an address
+pub(crate) fn nft_details(addr_str: &str, collection: &str, nonce: u64, decimals: u32) -> Result<ApiCall<NftDetails>> {
+    let address = ElrondAddress::new(addr_str)?;
+    let collection = collection.to_string();
+    Ok(ApiCall::new(
+        ApiRequest::get(format!("address/{}/nft/{}/nonce/{}", address, collection, nonce)),
+        move |response| {
+            let token_data = get_object(parse_response_data(response)?, "tokenData")?;
+            let identifier = esdt::nft_identifier(&collection, nonce);
+            let uris = match token_data.get("uris") {
+                Some(Value::Array(uris)) => uris
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(|uri| Ok(String::from_utf8_lossy(&decode_base64(uri)?).to_string()))
+                    .collect::<Result<Vec<String>>>()?,
+                _ => Vec::new()
+            };
+            let optional_bytes = |name: &str| match token_data.get(name).and_then(Value::as_str) {
+                Some(encoded) => decode_base64(encoded),
+                None => Ok(Vec::new())
+            };
+            let royalties = get_u64(token_data, "royalties")?;
+            if royalties > esdt::MAX_ROYALTIES as u64 {
+                return Err(ElrondClientError::new(&format!("royalties of {} exceed {} basis points", royalties, esdt::MAX_ROYALTIES)));
+            }
+            Ok(NftDetails {
+                collection: collection.clone(),
+                nonce,
+                name: token_data.get("name").and_then(Value::as_str).unwrap_or("").to_string(),
+                creator: ElrondAddress::new(get_str(token_data, "creator")?)?,
+                royalties: royalties as u32,
+                hash: optional_bytes("hash")?,
+                attributes: optional_bytes("attributes")?,
+                uris,
+                balance: TokenAmount::from_base_units(get_str(token_data, "balance")?, decimals, &identifier)?,
+                identifier
+            })
+        }
+    ))
+}
+
+/// Get the properties of an ESDT token from the ESDT system smart contract
+pub(crate) fn token_properties(identifier: &str) -> ApiCall<TokenProperties> {
+    let token = identifier.to_string();
+    ApiCall::new(
+        vm_query(&ElrondAddress::ESDT_SC, "getTokenProperties", &[identifier.as_bytes().to_vec()]),
+        move |response| esdt::parse_token_properties(&token, &parse_return_data(response)?)
+    )
+}
+
+/// Get the addresses holding special roles for an ESDT token
+pub(crate) fn token_roles(identifier: &str) -> ApiCall<Vec<TokenRoles>> {
+    ApiCall::new(
+        vm_query(&ElrondAddress::ESDT_SC, "getSpecialRoles", &[identifier.as_bytes().to_vec()]),
+        |response| esdt::parse_token_roles(&parse_return_data(response)?)
+    )
+}
+
+/// Query a delegation contract for an amount of eGLD concerning `delegator`, e.g., its active stake
+/// with "getUserActiveStake"
+pub(crate) fn delegation_amount(contract: &str, func_name: &str, delegator: &str) -> Result<ApiCall<ElrondCurrencyAmount>> {
+    let contract = ElrondAddress::new(contract)?;
+    let delegator = ElrondAddress::new(delegator)?;
+    Ok(ApiCall::new(
+        vm_query(&contract, func_name, &[delegator.to_bytes().to_vec()]),
+        |response| {
+            // a delegator without stake gets no return data
+            let amount = parse_return_data(response)?
+                .first()
+                .map(|bytes| BigUint::from_bytes_be(bytes))
+                .unwrap_or_default();
+            ElrondCurrencyAmount::from_denominated(&amount.to_string())
+        }
+    ))
+}
+
+/// Resolve a username (e.g., "alice.elrond") to the address that registered it, through its DNS
+/// contract
+pub(crate) fn resolve_username(name: &str) -> Result<ApiCall<ElrondAddress>> {
+    let name = dns::normalize_username(name)?;
+    let not_found = format!("username '{}' is not registered", name);
+    Ok(ApiCall::new(
+        vm_query(&dns::dns_address(&name)?, "resolve", &[name.as_bytes().to_vec()]),
+        move |response| match parse_return_data(response)?.first() {
+            Some(bytes) if !bytes.is_empty() => ElrondAddress::from_bytes(bytes),
+            _ => Err(ElrondClientError::new(&not_found))
+        }
+    ))
+}
+
+/// Get the username of an address, if it registered one
+pub(crate) fn address_username(addr_str: &str) -> Result<ApiCall<Option<String>>> {
+    let address = ElrondAddress::new(addr_str)?;
+    Ok(ApiCall::new(
+        ApiRequest::get(format!("address/{}/username", address)),
+        |response| {
+            let username = get_str(parse_response_data(response)?, "username")?;
+            Ok(Some(username.to_string()).filter(|username| !username.is_empty()))
+        }
+    ))
+}
+
+/// Get the nonce of the latest final hyperblock (metachain block)
+pub(crate) fn highest_final_nonce() -> ApiCall<u64> {
+    ApiCall::new(
+        ApiRequest::get(format!("network/status/{}", METACHAIN_SHARD)),
+        |response| get_u64(get_object(parse_response_data(response)?, "status")?, "erd_highest_final_nonce")
+    )
+}
+
+/// Get the hash and timestamp of a hyperblock, by nonce
+pub(crate) fn hyperblock_hash_by_nonce(nonce: u64) -> ApiCall<(String, u64)> {
+    ApiCall::new(
+        ApiRequest::get(format!("hyperblock/by-nonce/{}", nonce)),
+        parse_hyperblock_hash
+    )
+}
+
+/// Get the timestamp of a block of any shard or of the metachain, by hash. Unlike gateway routes,
+/// the API answers with the block itself rather than wrapping it in 'data'.
+pub(crate) fn block_timestamp(hash: &str) -> ApiCall<u64> {
+    ApiCall::new(
+        ApiRequest::get(format!("blocks/{}", hash)),
+        |response| {
+            let block = response.as_object().ok_or_else(|| {
+                ElrondClientError::new("response is not a JSON object")
+            })?;
+            get_u64(block, "timestamp")
+        }
+    )
+}
+
+/// Decode the hash and timestamp of a hyperblock
+fn parse_hyperblock_hash(response: &Value) -> Result<(String, u64)> {
+    let hyperblock = get_object(parse_response_data(response)?, "hyperblock")?;
+    Ok((get_str(hyperblock, "hash")?.to_string(), get_u64(hyperblock, "timestamp")?))
+}
+
+/// Decode an object of the response data into a typed struct
+fn parse_typed<T: DeserializeOwned>(response: &Value, name: &str) -> Result<T> {
+    let object = get_object(parse_response_data(response)?, name)?;
+    serde_json::from_value(Value::Object(object.clone())).map_err(|e| {
+        ElrondClientError::new(&format!("could not decode '{}': {}", name, e))
+    })
+}
+
+/// Get the progress of a shard, or of the metachain with `METACHAIN_SHARD`
+pub(crate) fn network_status(shard: u32) -> ApiCall<NetworkStatus> {
+    ApiCall::new(
+        ApiRequest::get(format!("network/status/{}", shard)),
+        |response| parse_typed(response, "status")
+    )
+}
+
+/// Get a block of a shard with its transactions, by nonce
+pub(crate) fn block_by_nonce(shard: u32, nonce: u64) -> ApiCall<Block> {
+    ApiCall::new(
+        ApiRequest::get(format!("block/{}/by-nonce/{}?withTxs=true", shard, nonce)),
+        |response| parse_typed(response, "block")
+    )
+}
+
+/// Get a block of a shard with its transactions, by hash
+pub(crate) fn block_by_hash(shard: u32, hash: &str) -> ApiCall<Block> {
+    ApiCall::new(
+        ApiRequest::get(format!("block/{}/by-hash/{}?withTxs=true", shard, hash)),
+        |response| parse_typed(response, "block")
+    )
+}
+
+/// Get a hyperblock with its transactions, by nonce
+pub(crate) fn hyperblock_by_nonce(nonce: u64) -> ApiCall<Hyperblock> {
+    ApiCall::new(
+        ApiRequest::get(format!("hyperblock/by-nonce/{}", nonce)),
+        |response| parse_typed(response, "hyperblock")
+    )
+}
+
+/// Get a hyperblock with its transactions, by hash
+pub(crate) fn hyperblock_by_hash(hash: &str) -> ApiCall<Hyperblock> {
+    ApiCall::new(
+        ApiRequest::get(format!("hyperblock/by-hash/{}", hash)),
+        |response| parse_typed(response, "hyperblock")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::{handle_response, address_nonce, address_balance, esdt_entries, esdt_collections, esdt_amounts};
+    use super::{esdt_balance, nft_details, token_properties, parse_return_data};
+    use super::super::transport::Method;
+
+    #[test]
+    fn parse_nonce_response() {
+        let call = address_nonce("erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k").unwrap();
+        assert_eq!(call.request.method, Method::Get);
+        assert_eq!(call.request.path, "address/erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k/nonce");
+        let response = handle_response(200, "{\"data\":{\"nonce\":12},\"code\":\"successful\"}").unwrap();
+        assert_eq!((call.parse)(&response).unwrap(), 12);
+    }
+
+    #[test]
+    fn parse_balance_response() {
+        let call = address_balance("erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k").unwrap();
+        let response = handle_response(200, "{\"data\":{\"balance\":\"100000000000000\"}}").unwrap();
+        assert_eq!((call.parse)(&response).unwrap().to_human().to_string(), "0.0001");
+    }
+
+    #[test]
+    fn error_status_is_error() {
+        let err = handle_response(404, "not found").unwrap_err();
+        assert_eq!(err.to_string(), "error code 404, data='not found'");
+    }
+
+    #[test]
+    fn parse_esdt_list() {
+        let call = esdt_entries("erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k").unwrap();
+        let response = handle_response(200, r#"{"data":{"esdts":{
+            "WEGLD-bd4d79":{"balance":"1500000000000000000","tokenIdentifier":"WEGLD-bd4d79"},
+            "NFT-123456-0a":{"balance":"1","nonce":10,"tokenIdentifier":"NFT-123456-0a"}
+        }}}"#).unwrap();
+        let entries = (call.parse)(&response).unwrap();
+        assert_eq!(esdt_collections(&entries), vec!["NFT-123456", "WEGLD-bd4d79"]);
+        let mut decimals = HashMap::new();
+        decimals.insert("WEGLD-bd4d79".to_string(), 18);
+        let amounts = esdt_amounts(entries, &decimals).unwrap();
+        assert_eq!(amounts[0].identifier(), "NFT-123456-0a");
+        assert_eq!(amounts[0].to_string(), "1");
+        assert_eq!(amounts[1].to_string(), "1.5");
+    }
+
+    #[test]
+    fn parse_esdt_balance() {
+        let call = esdt_balance("erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k", "USDC-c76f1f", 6).unwrap();
+        assert_eq!(call.request.path, "address/erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k/esdt/USDC-c76f1f");
+        let response = handle_response(200, r#"{"data":{"tokenData":{"balance":"2500000","tokenIdentifier":"USDC-c76f1f"}}}"#).unwrap();
+        let amount = (call.parse)(&response).unwrap();
+        assert_eq!(amount.to_string(), "2.5");
+        assert_eq!(amount.identifier(), "USDC-c76f1f");
+    }
+
+    #[test]
+    fn parse_nft_details() {
+        let call = nft_details("erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k", "NFT-123456", 10, 0).unwrap();
+        assert_eq!(call.request.path, "address/erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k/nft/NFT-123456/nonce/10");
+        let response = handle_response(200, r#"{"data":{"tokenData":{
+            "attributes":"bWV0YWRhdGE6YWJj","balance":"1","creator":"erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k",
+            "hash":"","name":"Cool NFT","nonce":10,"royalties":"750","tokenIdentifier":"NFT-123456-0a",
+            "uris":["aHR0cHM6Ly9leGFtcGxlLmNvbS8xLnBuZw=="]
+        }}}"#).unwrap();
+        let details = (call.parse)(&response).unwrap();
+        assert_eq!(details.identifier, "NFT-123456-0a");
+        assert_eq!(details.name, "Cool NFT");
+        assert_eq!(details.royalties, 750);
+        assert_eq!(details.attributes, b"metadata:abc".to_vec());
+        assert_eq!(details.uris, vec!["https://example.com/1.png"]);
+        assert!(details.hash.is_empty());
+        // royalties are never guessed
+        let with_royalties = |royalties: &str| handle_response(200, &format!(
+            r#"{{"data":{{"tokenData":{{"balance":"1","creator":"erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k"{}}}}}}}"#,
+            royalties
+        )).unwrap();
+        assert!((call.parse)(&with_royalties("")).is_err());
+        assert!((call.parse)(&with_royalties(r#","royalties":"lots""#)).is_err());
+        assert!((call.parse)(&with_royalties(r#","royalties":"10001""#)).is_err());
+        assert!((call.parse)(&with_royalties(r#","royalties":"4294968046""#)).is_err());
+        assert_eq!((call.parse)(&with_royalties(r#","royalties":10000"#)).unwrap().royalties, 10000);
+    }
+
+    #[test]
+    fn token_properties_query() {
+        let call = token_properties("USDC-c76f1f");
+        assert_eq!(call.request.method, Method::Post);
+        assert_eq!(call.request.path, "vm-values/query");
+        let data = call.request.data.as_ref().unwrap();
+        assert_eq!(data["funcName"], "getTokenProperties");
+        assert_eq!(data["args"][0], hex::encode("USDC-c76f1f"));
+    }
+
+    #[test]
+    fn failed_vm_query_is_error() {
+        let response = handle_response(200, r#"{"data":{"data":{"returnCode":"user error","returnMessage":"no ticker with given name"}}}"#).unwrap();
+        let err = parse_return_data(&response).unwrap_err();
+        assert_eq!(err.to_string(), "vm query failed with 'user error': 'no ticker with given name'");
+        let response = handle_response(200, r#"{"data":{"data":{"returnCode":"ok","returnData":["AQI=",null]}}}"#).unwrap();
+        assert_eq!(parse_return_data(&response).unwrap(), vec![vec![1, 2], vec![]]);
+    }
+}
@@ -0,0 +1,443 @@
+//! Async counterpart of `Client`, sending requests through an `AsyncTransport` which defaults to
+//! `reqwest`. Available with the `async` feature. Failover and retries are decided by the same
+//! dispatcher as the blocking client.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::future::{self, Future};
+use std::ops::Range;
+use std::pin::Pin;
+use std::time::Duration;
+use super::api::{self, ApiCall, ApiRequest, Dispatcher, Step};
+use super::transport::{Transport, MockTransport, Method, HttpRequest, HttpResponse};
+use super::retry::{RetryPolicy, RateLimiter};
+use super::endpoints::{EndpointPool, EndpointHealth};
+use super::MAINNET_ENDPOINT;
+use super::super::{SignedTransaction, ElrondAddress, ElrondCurrencyAmount, TokenAmount, Result, ElrondClientError};
+use super::super::esdt::{TokenProperties, TokenRoles, NftDetails};
+use super::super::blocks::{NetworkStatus, Block, Hyperblock};
+
+/// Response to come from an `AsyncTransport`
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<HttpResponse>> + Send + 'a>>;
+
+/// Timer of an `AsyncTransport`
+pub type SleepFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Async counterpart of `Transport`, sending HTTP requests on behalf of an `AsyncClient`. Failures
+/// to get any response at all are returned as errors, whereas any response from the server,
+/// whatever its status code, is returned as an `HttpResponse`.
+pub trait AsyncTransport {
+    fn send<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a>;
+    /// Wait for `duration`, before retries and to respect rate limits. The default uses the tokio
+    /// timer, which panics outside a tokio runtime: transports for other runtimes, e.g.,
+    /// async-std or smol, override it with their own timer.
+    fn sleep(&self, duration: Duration) -> SleepFuture {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// Default async transport, backed by `reqwest`, which needs a tokio runtime
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestTransport {
+    http: reqwest::Client
+}
+
+impl ReqwestTransport {
+    /// Create a new transport with a default `reqwest` client
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a new transport sending requests with `http`, e.g., one configured with a timeout
+    pub fn with_client(http: reqwest::Client) -> Self {
+        Self { http }
+    }
+}
+
+impl AsyncTransport for ReqwestTransport {
+    fn send<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a> {
+        Box::pin(async move {
+            let mut req = match request.method {
+                Method::Get => self.http.get(&request.url),
+                Method::Post => self.http.post(&request.url)
+            };
+            if let Some(json) = &request.body {
+                req = req.json(json);
+            }
+            let resp = req.send().await.map_err(|e| {
+                ElrondClientError::new(&format!("request failed: {}", e))
+            })?;
+            let status = resp.status().as_u16();
+            let headers = resp
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect();
+            let body = resp.text().await.map_err(|_| {
+                ElrondClientError::new("could not decode response to string")
+            })?;
+            Ok(HttpResponse { status, headers, body })
+        })
+    }
+}
+
+/// Scripted responses are ready right away, waiting uses the tokio timer
+impl AsyncTransport for MockTransport {
+    fn send<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a> {
+        Box::pin(future::ready(Transport::send(self, request)))
+    }
+}
+
+impl<T: AsyncTransport + ?Sized> AsyncTransport for &T {
+    fn send<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a> {
+        (**self).send(request)
+    }
+    fn sleep(&self, duration: Duration) -> SleepFuture {
+        (**self).sleep(duration)
+    }
+}
+
+/// Client manages interactions with the Elrond network without blocking the current thread.
+/// Requests are sent through an `AsyncTransport`, which defaults to `reqwest`.
+pub struct AsyncClient<T: AsyncTransport = ReqwestTransport> {
+    dispatcher: Dispatcher,
+    transport: T
+}
+
+impl<T: AsyncTransport> AsyncClient<T> {
+    /// Internal helper for submitting requests to the network, failing over and retrying as
+    /// decided by the dispatcher
+    async fn request(&self, request: &ApiRequest) -> Result<serde_json::Value> {
+        let mut attempts = self.dispatcher.attempts(request);
+        loop {
+            let index = attempts.endpoint();
+            let outcome = self.send_to(index, request).await;
+            match attempts.next(index, &outcome) {
+                Step::Failover => {},
+                Step::Retry(delay) => self.transport.sleep(delay).await,
+                Step::Done => return api::handle_outcome(outcome)
+            }
+        }
+    }
+
+    /// Internal helper for sending a request once to endpoint `index`, recording its health
+    async fn send_to(&self, index: usize, request: &ApiRequest) -> Result<HttpResponse> {
+        if let Some(delay) = self.dispatcher.pace() {
+            self.transport.sleep(delay).await;
+        }
+        let outcome = self.transport.send(&self.dispatcher.to_http(index, request)).await;
+        self.dispatcher.record(index, &outcome);
+        outcome
+    }
+
+    /// Internal helper for submitting a request and parsing its response
+    async fn call<R>(&self, call: ApiCall<R>) -> Result<R> {
+        let response = self.request(&call.request).await?;
+        (call.parse)(&response)
+    }
+
+    /// Internal helper for submitting a request to every healthy endpoint, checking that those
+    /// answering all agree on the parsed result
+    async fn call_consistent<R: PartialEq + Debug>(&self, call: ApiCall<R>) -> Result<R> {
+        let mut outcomes = Vec::new();
+        for index in self.dispatcher.endpoints.healthy() {
+            outcomes.push((index, self.send_to(index, &call.request).await));
+        }
+        self.dispatcher.agreed_result(&call, outcomes)
+    }
+}
+
+impl Default for AsyncClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsyncClient {
+    /// Create a new async client that will work on Elrond MainNet
+    pub fn new() -> Self {
+        Self::with_endpoint(MAINNET_ENDPOINT)
+    }
+
+    /// Create a new async client that sends requests to `endpoint`
+    pub fn with_endpoint(endpoint: &str) -> Self {
+        Self::with_transport(endpoint, ReqwestTransport::new())
+    }
+}
+
+impl<T: AsyncTransport> AsyncClient<T> {
+    /// Create a new async client that sends requests to `endpoint` through a custom transport
+    pub fn with_transport(endpoint: &str, transport: T) -> Self {
+        Self::with_endpoints(EndpointPool::single(endpoint), transport)
+    }
+
+    /// Create a new async client that spreads requests over a pool of endpoints, failing over
+    /// from one to the next when they stop responding
+    pub fn with_endpoints(endpoints: EndpointPool, transport: T) -> Self {
+        Self { dispatcher: Dispatcher::new(endpoints), transport }
+    }
+
+    /// Retry failed requests that are safe to replay according to `retry_policy`
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.dispatcher.retry_policy = retry_policy;
+        self
+    }
+
+    /// Limit the rate at which the client sends requests
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.dispatcher.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Get the transport used by the client
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Get the endpoints used by the client
+    pub fn endpoints(&self) -> &EndpointPool {
+        &self.dispatcher.endpoints
+    }
+
+    /// Ping every endpoint (including those currently skipped) and return their updated health
+    pub async fn check_health(&self) -> Vec<EndpointHealth> {
+        for index in 0..self.dispatcher.endpoints.len() {
+            // failures are recorded in the pool, nothing else to do with them here
+            let _ = self.send_to(index, &api::health_check()).await;
+        }
+        self.dispatcher.endpoints.health()
+    }
+
+    /// Get the current nonce associated with an address
+    pub async fn get_address_nonce(&self, addr_str: &str) -> Result<u64> {
+        self.call(api::address_nonce(addr_str)?).await
+    }
+
+    /// Get the current nonce associated with an address from every healthy endpoint, failing if
+    /// they do not all agree
+    pub async fn get_address_nonce_consistent(&self, addr_str: &str) -> Result<u64> {
+        self.call_consistent(api::address_nonce(addr_str)?).await
+    }
+
+    /// Post signed transaction to Elrond network and return hash of transaction
+    pub async fn post_signed_transaction(&self, signed_tx: SignedTransaction) -> Result<String> {
+        self.call(api::send_transaction(&signed_tx)?).await
+    }
+
+    /// Get the status of a transaction, e.g., "pending", "success" or "fail"
+    pub async fn get_transaction_status(&self, tx_hash: &str) -> Result<String> {
+        self.call(api::transaction_status(tx_hash)).await
+    }
+
+    /// Get the balance associated with an Elrond address
+    pub async fn get_address_balance(&self, addr_str: &str) -> Result<ElrondCurrencyAmount> {
+        self.call(api::address_balance(addr_str)?).await
+    }
+
+    /// Get every ESDT token (fungible or not) held by an address. The properties of each
+    /// collection are looked up to apply the right decimals.
+    pub async fn get_esdt_tokens(&self, addr_str: &str) -> Result<Vec<TokenAmount>> {
+        let entries = self.call(api::esdt_entries(addr_str)?).await?;
+        let mut decimals = HashMap::new();
+        for collection in api::esdt_collections(&entries) {
+            let properties = self.call(api::token_properties(&collection)).await?;
+            decimals.insert(collection, properties.decimals);
+        }
+        api::esdt_amounts(entries, &decimals)
+    }
+
+    /// Get the balance of an ESDT token held by an address
+    pub async fn get_esdt_balance(&self, addr_str: &str, identifier: &str) -> Result<TokenAmount> {
+        let properties = self.call(api::token_properties(identifier)).await?;
+        self.call(api::esdt_balance(addr_str, identifier, properties.decimals)?).await
+    }
+
+    /// Get the details of the NFT, SFT or MetaESDT with the given nonce in a collection,
+    /// as held by an address
+    pub async fn get_nft_details(&self, addr_str: &str, collection: &str, nonce: u64) -> Result<NftDetails> {
+        let properties = self.call(api::token_properties(collection)).await?;
+        self.call(api::nft_details(addr_str, collection, nonce, properties.decimals)?).await
+    }
+
+    /// Get the properties of an ESDT token (or collection), e.g., its decimals and owner
+    pub async fn get_token_properties(&self, identifier: &str) -> Result<TokenProperties> {
+        self.call(api::token_properties(identifier)).await
+    }
+
+    /// Get the addresses holding special roles for an ESDT token (or collection)
+    pub async fn get_token_roles(&self, identifier: &str) -> Result<Vec<TokenRoles>> {
+        self.call(api::token_roles(identifier)).await
+    }
+
+    /// Get the eGLD `delegator` has actively staked with the delegation contract `contract`
+    pub async fn get_delegated_stake(&self, contract: &str, delegator: &str) -> Result<ElrondCurrencyAmount> {
+        self.call(api::delegation_amount(contract, "getUserActiveStake", delegator)?).await
+    }
+
+    /// Get the eGLD `delegator` undelegated from `contract`, still in its unbonding period or
+    /// ready to be withdrawn
+    pub async fn get_undelegated_stake(&self, contract: &str, delegator: &str) -> Result<ElrondCurrencyAmount> {
+        self.call(api::delegation_amount(contract, "getUserUnStakedValue", delegator)?).await
+    }
+
+    /// Get the rewards `delegator` can claim from the delegation contract `contract`
+    pub async fn get_claimable_rewards(&self, contract: &str, delegator: &str) -> Result<ElrondCurrencyAmount> {
+        self.call(api::delegation_amount(contract, "getClaimableRewards", delegator)?).await
+    }
+
+    /// Resolve a username (herotag), e.g., "alice.elrond" or "alice", to the address that
+    /// registered it
+    pub async fn resolve_username(&self, name: &str) -> Result<ElrondAddress> {
+        self.call(api::resolve_username(name)?).await
+    }
+
+    /// Get the username (herotag) registered by an address, if any
+    pub async fn get_address_username(&self, addr_str: &str) -> Result<Option<String>> {
+        self.call(api::address_username(addr_str)?).await
+    }
+
+    /// Get the hash of the latest final hyperblock (metachain block)
+    pub async fn get_latest_block_hash(&self) -> Result<String> {
+        let nonce = self.call(api::highest_final_nonce()).await?;
+        Ok(self.call(api::hyperblock_hash_by_nonce(nonce)).await?.0)
+    }
+
+    /// Get the timestamp (in seconds) of the block of any shard, or of the metachain, with the
+    /// given hash
+    pub async fn get_block_timestamp(&self, hash: &str) -> Result<u64> {
+        self.call(api::block_timestamp(hash)).await
+    }
+
+    /// Get the progress of a shard (current round, epoch and nonce), or of the metachain with
+    /// `METACHAIN_SHARD`
+    pub async fn get_network_status(&self, shard: u32) -> Result<NetworkStatus> {
+        self.call(api::network_status(shard)).await
+    }
+
+    /// Get the block of `shard` with the given nonce, with its transactions
+    pub async fn get_block_by_nonce(&self, shard: u32, nonce: u64) -> Result<Block> {
+        self.call(api::block_by_nonce(shard, nonce)).await
+    }
+
+    /// Get the block of `shard` with the given hash, with its transactions
+    pub async fn get_block_by_hash(&self, shard: u32, hash: &str) -> Result<Block> {
+        self.call(api::block_by_hash(shard, hash)).await
+    }
+
+    /// Get the hyperblock with the given nonce, with the transactions of every shard
+    pub async fn get_hyperblock_by_nonce(&self, nonce: u64) -> Result<Hyperblock> {
+        self.call(api::hyperblock_by_nonce(nonce)).await
+    }
+
+    /// Get the hyperblock with the given hash, with the transactions of every shard
+    pub async fn get_hyperblock_by_hash(&self, hash: &str) -> Result<Hyperblock> {
+        self.call(api::hyperblock_by_hash(hash)).await
+    }
+
+    /// Get the hyperblocks with nonces in the given range
+    pub async fn get_hyperblocks(&self, nonces: Range<u64>) -> Result<Vec<Hyperblock>> {
+        let mut hyperblocks = Vec::new();
+        for nonce in nonces {
+            hyperblocks.push(self.get_hyperblock_by_nonce(nonce).await?);
+        }
+        Ok(hyperblocks)
+    }
+
+    /// Get the final hyperblocks from nonce `start`, at most `limit` of them. Resume from the
+    /// nonce following the last one returned.
+    pub async fn get_final_hyperblocks(&self, start: u64, limit: usize) -> Result<Vec<Hyperblock>> {
+        let final_nonce = self.call(api::highest_final_nonce()).await?;
+        let end = final_nonce.saturating_add(1).min(start.saturating_add(limit as u64));
+        self.get_hyperblocks(start..end.max(start)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::task::{Context, Poll, Waker};
+    use std::time::Duration;
+    use super::{AsyncClient, AsyncTransport, SleepFuture, TransportFuture, MockTransport, HttpRequest, HttpResponse, RetryPolicy};
+    use serde_json::json;
+
+    const ADDRESS: &str = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap().block_on(future)
+    }
+
+    fn retrying_client<T: AsyncTransport>(transport: T, base_delay: Duration) -> AsyncClient<T> {
+        AsyncClient::with_transport("https://api.elrond.com", transport)
+            .with_retry_policy(RetryPolicy::new(3).with_base_delay(base_delay).with_jitter(false))
+    }
+
+    #[test]
+    pub fn futures_resolve() {
+        let client = AsyncClient::with_transport("https://api.elrond.com", MockTransport::new());
+        client.transport()
+            .push_json(&json!({"data": {"nonce": 7}, "code": "successful"}))
+            .push_json(&json!({"data": {"balance": "100000000000000"}, "code": "successful"}));
+        assert_eq!(block_on(client.get_address_nonce(ADDRESS)).unwrap(), 7);
+        assert_eq!(block_on(client.get_address_balance(ADDRESS)).unwrap().to_human().to_string(), "0.0001");
+        assert_eq!(client.transport().requests().len(), 2);
+    }
+
+    #[test]
+    pub fn sleeping_does_not_block_runtime() {
+        let client = retrying_client(MockTransport::new(), Duration::from_millis(100));
+        client.transport()
+            .push_response(503, "unavailable")
+            .push_json(&json!({"data": {"nonce": 3}}));
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let nonce = block_on(async {
+            let counter = ticks.clone();
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    counter.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+            client.get_address_nonce(ADDRESS).await
+        });
+        assert_eq!(nonce.unwrap(), 3);
+        // the other task kept running while the client waited before retrying
+        assert!(ticks.load(Ordering::SeqCst) >= 5);
+    }
+
+    /// Transport with its own timer, recording delays instead of waiting
+    #[derive(Default)]
+    struct NoTimerTransport {
+        mock: MockTransport,
+        sleeps: Mutex<Vec<Duration>>
+    }
+
+    impl AsyncTransport for NoTimerTransport {
+        fn send<'a>(&'a self, request: &'a HttpRequest) -> TransportFuture<'a> {
+            AsyncTransport::send(&self.mock, request)
+        }
+        fn sleep(&self, duration: Duration) -> SleepFuture {
+            self.sleeps.lock().unwrap().push(duration);
+            Box::pin(std::future::ready(()))
+        }
+    }
+
+    #[test]
+    pub fn custom_timer_needs_no_tokio_runtime() {
+        let client = retrying_client(NoTimerTransport::default(), Duration::from_millis(100));
+        client.transport().mock
+            .push_http_response(HttpResponse::new(429, "too many requests").with_header("Retry-After", "2"))
+            .push_json(&json!({"data": {"nonce": 3}}));
+        // poll by hand, outside of any runtime
+        let mut future = Box::pin(client.get_address_nonce(ADDRESS));
+        let mut context = Context::from_waker(Waker::noop());
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(nonce) => assert_eq!(nonce.unwrap(), 3),
+            Poll::Pending => panic!("scripted requests should resolve right away")
+        }
+        assert_eq!(*client.transport().sleeps.lock().unwrap(), vec![Duration::from_secs(2)]);
+    }
+}
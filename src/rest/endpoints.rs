@@ -0,0 +1,230 @@
+//! Pool of gateway endpoints used by clients, with failover and circuit breaking. Endpoints are
+//! listed in order of preference, and requests go to the first one that is considered healthy.
+
+use std::fmt::Debug;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use super::super::{ElrondClientError, Result};
+
+/// Health of an endpoint as seen by the client
+#[derive(Clone, Debug, PartialEq)]
+pub struct EndpointHealth {
+    pub url: String,
+    pub healthy: bool,
+    pub consecutive_failures: u32
+}
+
+/// Internal health bookkeeping for one endpoint
+#[derive(Clone, Debug)]
+struct EndpointState {
+    url: String,
+    consecutive_failures: u32,
+    // circuit is open (endpoint skipped) until this instant
+    open_until: Option<Instant>
+}
+
+impl EndpointState {
+    fn is_healthy(&self, now: Instant) -> bool {
+        match self.open_until {
+            Some(open_until) => now >= open_until,
+            None => true
+        }
+    }
+}
+
+/// A list of endpoints serving the same network. After `failure_threshold` consecutive failures an
+/// endpoint's circuit opens and it is skipped for `cooldown`, after which it is tried again.
+#[derive(Debug)]
+pub struct EndpointPool {
+    states: Mutex<Vec<EndpointState>>,
+    failure_threshold: u32,
+    cooldown: Duration
+}
+
+impl EndpointPool {
+    /// Create a pool from endpoints in order of preference, opening circuits after 3 consecutive
+    /// failures for 30s
+    pub fn new(endpoints: &[&str]) -> Result<Self> {
+        if endpoints.is_empty() {
+            return Err(ElrondClientError::new("endpoint pool needs at least one endpoint"));
+        }
+        Ok(Self::from_urls(endpoints))
+    }
+    /// Pool of a single endpoint
+    pub(crate) fn single(endpoint: &str) -> Self {
+        Self::from_urls(&[endpoint])
+    }
+    fn from_urls(endpoints: &[&str]) -> Self {
+        let states = endpoints
+            .iter()
+            .map(|url| EndpointState {
+                url: url.trim_end_matches('/').to_string(),
+                consecutive_failures: 0,
+                open_until: None
+            })
+            .collect();
+        Self {
+            states: Mutex::new(states),
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(30)
+        }
+    }
+    /// Set the number of consecutive failures after which an endpoint is skipped
+    pub fn with_failure_threshold(mut self, failure_threshold: u32) -> Self {
+        self.failure_threshold = failure_threshold.max(1);
+        self
+    }
+    /// Set how long an endpoint is skipped once its circuit opens
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+    /// Number of endpoints in the pool
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+    /// Whether the pool is empty, which never happens for a pool built with `new`
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+    /// Current health of every endpoint, in order of preference
+    pub fn health(&self) -> Vec<EndpointHealth> {
+        let now = Instant::now();
+        self.lock()
+            .iter()
+            .map(|state| EndpointHealth {
+                url: state.url.clone(),
+                healthy: state.is_healthy(now),
+                consecutive_failures: state.consecutive_failures
+            })
+            .collect()
+    }
+    /// URL of endpoint `index`
+    pub(crate) fn url(&self, index: usize) -> String {
+        self.lock()[index].url.clone()
+    }
+    /// Indices of endpoints that are currently healthy
+    pub(crate) fn healthy(&self) -> Vec<usize> {
+        let now = Instant::now();
+        self.lock()
+            .iter()
+            .enumerate()
+            .filter(|(_, state)| state.is_healthy(now))
+            .map(|(index, _)| index)
+            .collect()
+    }
+    /// Pick the endpoint for the next attempt: the first healthy endpoint not in `tried`, else the
+    /// first healthy endpoint, else the endpoint whose circuit closes soonest
+    pub(crate) fn select(&self, tried: &[usize]) -> usize {
+        let now = Instant::now();
+        let states = self.lock();
+        let healthy: Vec<usize> = (0..states.len())
+            .filter(|&index| states[index].is_healthy(now))
+            .collect();
+        healthy
+            .iter()
+            .find(|index| !tried.contains(index))
+            .or_else(|| healthy.first())
+            .copied()
+            .unwrap_or_else(|| {
+                (0..states.len())
+                    .min_by_key(|&index| states[index].open_until)
+                    .unwrap_or(0)
+            })
+    }
+    /// Whether a healthy endpoint that is not in `tried` is available for failover
+    pub(crate) fn has_alternative(&self, tried: &[usize]) -> bool {
+        self.healthy().iter().any(|index| !tried.contains(index))
+    }
+    /// Record the outcome of a request sent to endpoint `index`. Throttled requests are not
+    /// failures, the endpoint being up.
+    pub(crate) fn record(&self, index: usize, failed: bool) {
+        let now = Instant::now();
+        let mut states = self.lock();
+        let state = &mut states[index];
+        if failed {
+            state.consecutive_failures += 1;
+            if state.consecutive_failures >= self.failure_threshold {
+                state.open_until = Some(now + self.cooldown);
+            }
+        } else {
+            state.consecutive_failures = 0;
+            state.open_until = None;
+        }
+    }
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<EndpointState>> {
+        self.states.lock().expect("endpoint pool lock poisoned")
+    }
+}
+
+/// Result of a request sent to every healthy endpoint, given the results of the endpoints that
+/// answered and the last error of those that failed, which are skipped. The endpoints that
+/// answered must agree.
+pub(crate) fn agreed_result<R: PartialEq + Debug>(mut results: Vec<(String, R)>, last_error: Option<ElrondClientError>) -> Result<R> {
+    if results.iter().any(|(_, result)| result != &results[0].1) {
+        return Err(ElrondClientError::new(
+            &format!("endpoints disagree: {:?}", results)
+        ));
+    }
+    match results.pop() {
+        Some((_, result)) => Ok(result),
+        None => Err(last_error.unwrap_or_else(|| ElrondClientError::new("no healthy endpoint available")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::EndpointPool;
+
+    #[test]
+    fn needs_an_endpoint() {
+        assert!(EndpointPool::new(&[]).is_err());
+    }
+
+    #[test]
+    fn prefers_first_healthy_endpoint() {
+        let pool = EndpointPool::new(&["https://a", "https://b/", "https://c"]).unwrap().with_failure_threshold(2);
+        assert_eq!(pool.select(&[]), 0);
+        assert_eq!(pool.select(&[0]), 1);
+        assert_eq!(pool.url(1), "https://b");
+        pool.record(0, true);
+        assert_eq!(pool.select(&[]), 0);
+        pool.record(0, true);
+        assert_eq!(pool.select(&[]), 1);
+        assert!(pool.has_alternative(&[1]));
+        assert!(!pool.has_alternative(&[1, 2]));
+        assert_eq!(pool.select(&[1, 2]), 1);
+    }
+
+    #[test]
+    fn success_closes_circuit() {
+        let pool = EndpointPool::new(&["https://a", "https://b"]).unwrap().with_failure_threshold(1);
+        pool.record(0, true);
+        assert!(!pool.health()[0].healthy);
+        assert_eq!(pool.health()[0].consecutive_failures, 1);
+        pool.record(0, false);
+        assert!(pool.health()[0].healthy);
+        assert_eq!(pool.health()[0].consecutive_failures, 0);
+    }
+
+    #[test]
+    fn circuit_reopens_after_cooldown() {
+        let pool = EndpointPool::new(&["https://a", "https://b"]).unwrap()
+            .with_failure_threshold(1)
+            .with_cooldown(Duration::from_millis(0));
+        pool.record(0, true);
+        assert_eq!(pool.select(&[]), 0);
+    }
+
+    #[test]
+    fn all_open_picks_soonest_to_close() {
+        let pool = EndpointPool::new(&["https://a", "https://b"]).unwrap()
+            .with_failure_threshold(1)
+            .with_cooldown(Duration::from_secs(60));
+        pool.record(1, true);
+        pool.record(0, true);
+        assert!(pool.healthy().is_empty());
+        assert_eq!(pool.select(&[]), 1);
+    }
+}
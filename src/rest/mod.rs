@@ -0,0 +1,690 @@
+//! An HTTP client for interaction with the Elrond network.
+
+mod api;
+mod transport;
+mod retry;
+mod endpoints;
+#[cfg(feature = "async")]
+mod async_client;
+
+use super::{SignedTransaction, ElrondAddress, ElrondCurrencyAmount, TokenAmount, Result};
+use super::esdt::{TokenProperties, TokenRoles, NftDetails};
+use super::blocks::{NetworkStatus, Block, Hyperblock};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::ops::Range;
+use std::thread;
+use api::{ApiCall, ApiRequest, Dispatcher, Step};
+pub use transport::{Transport, UreqTransport, MockTransport, HttpRequest, HttpResponse, Method};
+pub use retry::{RetryPolicy, RateLimiter};
+pub use endpoints::{EndpointPool, EndpointHealth};
+#[cfg(feature = "async")]
+pub use async_client::{AsyncClient, AsyncTransport, ReqwestTransport, TransportFuture, SleepFuture};
+
+/// Default endpoint used by clients, Elrond MainNet
+pub(crate) const MAINNET_ENDPOINT: &str = "https://api.elrond.com";
+
+/// Client manages interactions with the Elrond network. Requests are sent through a `Transport`,
+/// which defaults to blocking `ureq` requests, to the healthiest of one or more endpoints.
+pub struct Client<T: Transport = UreqTransport> {
+    dispatcher: Dispatcher,
+    transport: T
+}
+
+impl<T: Transport> Client<T> {
+    /// Internal helper for submitting requests to the network, failing over and retrying as
+    /// decided by the dispatcher
+    fn request(&self, request: &ApiRequest) -> Result<serde_json::Value> {
+        let mut attempts = self.dispatcher.attempts(request);
+        loop {
+            let index = attempts.endpoint();
+            let outcome = self.send_to(index, request);
+            match attempts.next(index, &outcome) {
+                Step::Failover => {},
+                Step::Retry(delay) => thread::sleep(delay),
+                Step::Done => return api::handle_outcome(outcome)
+            }
+        }
+    }
+
+    /// Internal helper for sending a request once to endpoint `index`, recording its health
+    fn send_to(&self, index: usize, request: &ApiRequest) -> Result<HttpResponse> {
+        if let Some(delay) = self.dispatcher.pace() {
+            thread::sleep(delay);
+        }
+        let outcome = self.transport.send(&self.dispatcher.to_http(index, request));
+        self.dispatcher.record(index, &outcome);
+        outcome
+    }
+
+    /// Internal helper for submitting a request and parsing its response
+    fn call<R>(&self, call: ApiCall<R>) -> Result<R> {
+        let response = self.request(&call.request)?;
+        (call.parse)(&response)
+    }
+
+    /// Internal helper for submitting a request to every healthy endpoint, checking that those
+    /// answering all agree on the parsed result
+    fn call_consistent<R: PartialEq + Debug>(&self, call: ApiCall<R>) -> Result<R> {
+        let outcomes = self.dispatcher.endpoints.healthy()
+            .into_iter()
+            .map(|index| (index, self.send_to(index, &call.request)))
+            .collect();
+        self.dispatcher.agreed_result(&call, outcomes)
+    }
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Client {
+    /// Create a new client that will work on Elrond MainNet
+    pub fn new() -> Self {
+        Self::with_transport(MAINNET_ENDPOINT, UreqTransport::new())
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Create a new client that sends requests to `endpoint` through a custom transport
+    pub fn with_transport(endpoint: &str, transport: T) -> Self {
+        Self::with_endpoints(EndpointPool::single(endpoint), transport)
+    }
+
+    /// Create a new client that spreads requests over a pool of endpoints, failing over from
+    /// one to the next when they stop responding
+    pub fn with_endpoints(endpoints: EndpointPool, transport: T) -> Self {
+        Self { dispatcher: Dispatcher::new(endpoints), transport }
+    }
+
+    /// Retry failed requests that are safe to replay according to `retry_policy`
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.dispatcher.retry_policy = retry_policy;
+        self
+    }
+
+    /// Limit the rate at which the client sends requests
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.dispatcher.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Get the transport used by the client
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Get the endpoints used by the client
+    pub fn endpoints(&self) -> &EndpointPool {
+        &self.dispatcher.endpoints
+    }
+
+    /// Ping every endpoint (including those currently skipped) and return their updated health
+    pub fn check_health(&self) -> Vec<EndpointHealth> {
+        for index in 0..self.dispatcher.endpoints.len() {
+            // failures are recorded in the pool, nothing else to do with them here
+            let _ = self.send_to(index, &api::health_check());
+        }
+        self.dispatcher.endpoints.health()
+    }
+
+    /// Get the current nonce associated with an address
+    pub fn get_address_nonce(&self, addr_str: &str) -> Result<u64> {
+        self.call(api::address_nonce(addr_str)?)
+    }
+
+    /// Get the current nonce associated with an address from every healthy endpoint, failing if
+    /// they do not all agree
+    pub fn get_address_nonce_consistent(&self, addr_str: &str) -> Result<u64> {
+        self.call_consistent(api::address_nonce(addr_str)?)
+    }
+
+    /// Post signed transaction to Elrond network and return hash of transaction
+    pub fn post_signed_transaction(&self, signed_tx: SignedTransaction) -> Result<String>{
+        self.call(api::send_transaction(&signed_tx)?)
+    }
+
+    /// Get the status of a transaction, e.g., "pending", "success" or "fail"
+    pub fn get_transaction_status(&self, tx_hash: &str) -> Result<String> {
+        self.call(api::transaction_status(tx_hash))
+    }
+
+    /// Get the balance associated with an Elrond address
+    pub fn get_address_balance(&self, addr_str: &str) -> Result<ElrondCurrencyAmount> {
+        self.call(api::address_balance(addr_str)?)
+    }
+
+    /// Get every ESDT token (fungible or not) held by an address. The properties of each
+    /// collection are looked up to apply the right decimals.
+    pub fn get_esdt_tokens(&self, addr_str: &str) -> Result<Vec<TokenAmount>> {
+        let entries = self.call(api::esdt_entries(addr_str)?)?;
+        let mut decimals = HashMap::new();
+        for collection in api::esdt_collections(&entries) {
+            let properties = self.call(api::token_properties(&collection))?;
+            decimals.insert(collection, properties.decimals);
+        }
+        api::esdt_amounts(entries, &decimals)
+    }
+
+    /// Get the balance of an ESDT token held by an address
+    pub fn get_esdt_balance(&self, addr_str: &str, identifier: &str) -> Result<TokenAmount> {
+        let properties = self.call(api::token_properties(identifier))?;
+        self.call(api::esdt_balance(addr_str, identifier, properties.decimals)?)
+    }
+
+    /// Get the details of the NFT, SFT or MetaESDT with the given nonce in a collection,
+    /// as held by an address
+    pub fn get_nft_details(&self, addr_str: &str, collection: &str, nonce: u64) -> Result<NftDetails> {
+        let properties = self.call(api::token_properties(collection))?;
+        self.call(api::nft_details(addr_str, collection, nonce, properties.decimals)?)
+    }
+
+    /// Get the properties of an ESDT token (or collection), e.g., its decimals and owner
+    pub fn get_token_properties(&self, identifier: &str) -> Result<TokenProperties> {
+        self.call(api::token_properties(identifier))
+    }
+
+    /// Get the addresses holding special roles for an ESDT token (or collection)
+    pub fn get_token_roles(&self, identifier: &str) -> Result<Vec<TokenRoles>> {
+        self.call(api::token_roles(identifier))
+    }
+
+    /// Get the eGLD `delegator` has actively staked with the delegation contract `contract`
+    pub fn get_delegated_stake(&self, contract: &str, delegator: &str) -> Result<ElrondCurrencyAmount> {
+        self.call(api::delegation_amount(contract, "getUserActiveStake", delegator)?)
+    }
+
+    /// Get the eGLD `delegator` undelegated from `contract`, still in its unbonding period or
+    /// ready to be withdrawn
+    pub fn get_undelegated_stake(&self, contract: &str, delegator: &str) -> Result<ElrondCurrencyAmount> {
+        self.call(api::delegation_amount(contract, "getUserUnStakedValue", delegator)?)
+    }
+
+    /// Get the rewards `delegator` can claim from the delegation contract `contract`
+    pub fn get_claimable_rewards(&self, contract: &str, delegator: &str) -> Result<ElrondCurrencyAmount> {
+        self.call(api::delegation_amount(contract, "getClaimableRewards", delegator)?)
+    }
+
+    /// Resolve a username (herotag), e.g., "alice.elrond" or "alice", to the address that
+    /// registered it
+    pub fn resolve_username(&self, name: &str) -> Result<ElrondAddress> {
+        self.call(api::resolve_username(name)?)
+    }
+
+    /// Get the username (herotag) registered by an address, if any
+    pub fn get_address_username(&self, addr_str: &str) -> Result<Option<String>> {
+        self.call(api::address_username(addr_str)?)
+    }
+
+    /// Get the hash of the latest final hyperblock (metachain block)
+    pub fn get_latest_block_hash(&self) -> Result<String> {
+        let nonce = self.call(api::highest_final_nonce())?;
+        Ok(self.call(api::hyperblock_hash_by_nonce(nonce))?.0)
+    }
+
+    /// Get the timestamp (in seconds) of the block of any shard, or of the metachain, with the
+    /// given hash
+    pub fn get_block_timestamp(&self, hash: &str) -> Result<u64> {
+        self.call(api::block_timestamp(hash))
+    }
+
+    /// Get the progress of a shard (current round, epoch and nonce), or of the metachain with
+    /// `METACHAIN_SHARD`
+    pub fn get_network_status(&self, shard: u32) -> Result<NetworkStatus> {
+        self.call(api::network_status(shard))
+    }
+
+    /// Get the block of `shard` with the given nonce, with its transactions
+    pub fn get_block_by_nonce(&self, shard: u32, nonce: u64) -> Result<Block> {
+        self.call(api::block_by_nonce(shard, nonce))
+    }
+
+    /// Get the block of `shard` with the given hash, with its transactions
+    pub fn get_block_by_hash(&self, shard: u32, hash: &str) -> Result<Block> {
+        self.call(api::block_by_hash(shard, hash))
+    }
+
+    /// Get the hyperblock with the given nonce, with the transactions of every shard
+    pub fn get_hyperblock_by_nonce(&self, nonce: u64) -> Result<Hyperblock> {
+        self.call(api::hyperblock_by_nonce(nonce))
+    }
+
+    /// Get the hyperblock with the given hash, with the transactions of every shard
+    pub fn get_hyperblock_by_hash(&self, hash: &str) -> Result<Hyperblock> {
+        self.call(api::hyperblock_by_hash(hash))
+    }
+
+    /// Get the hyperblocks with nonces in the given range
+    pub fn get_hyperblocks(&self, nonces: Range<u64>) -> Result<Vec<Hyperblock>> {
+        nonces.map(|nonce| self.get_hyperblock_by_nonce(nonce)).collect()
+    }
+
+    /// Iterate over the final hyperblocks, starting with the one at nonce `start`. The iterator
+    /// ends once it has caught up with the highest final nonce, and can be resumed later from
+    /// `FinalHyperblocks::next_nonce`.
+    pub fn final_hyperblocks(&self, start: u64) -> FinalHyperblocks<'_, T> {
+        FinalHyperblocks { client: self, next_nonce: start, final_nonce: None }
+    }
+}
+
+/// Iterator over final hyperblocks, see `Client::final_hyperblocks`. Failed requests are yielded
+/// as errors, and retried by the next call to `next`.
+pub struct FinalHyperblocks<'a, T: Transport> {
+    client: &'a Client<T>,
+    next_nonce: u64,
+    // highest final nonce last seen
+    final_nonce: Option<u64>
+}
+
+impl<'a, T: Transport> FinalHyperblocks<'a, T> {
+    /// Nonce of the next hyperblock to be yielded
+    pub fn next_nonce(&self) -> u64 {
+        self.next_nonce
+    }
+
+    /// Whether the next hyperblock is known to be final
+    fn is_behind_final(&self) -> bool {
+        matches!(self.final_nonce, Some(final_nonce) if self.next_nonce <= final_nonce)
+    }
+}
+
+impl<'a, T: Transport> Iterator for FinalHyperblocks<'a, T> {
+    type Item = Result<Hyperblock>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.is_behind_final() {
+            match self.client.call(api::highest_final_nonce()) {
+                Ok(final_nonce) => self.final_nonce = Some(final_nonce),
+                Err(e) => return Some(Err(e))
+            }
+            if !self.is_behind_final() {
+                return None;
+            }
+        }
+        let hyperblock = self.client.get_hyperblock_by_nonce(self.next_nonce);
+        if hyperblock.is_ok() {
+            self.next_nonce += 1;
+        }
+        Some(hyperblock)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::{Client, MockTransport, Method, HttpResponse, RetryPolicy, RateLimiter, EndpointPool};
+    use super::api::ApiRequest;
+    use super::super::account::{Account, ElrondAddress};
+    use super::super::transaction::{UnsignedTransaction, Network};
+    use serde_json::json;
+
+    fn mock_client() -> Client<MockTransport> {
+        Client::with_transport("https://api.elrond.com", MockTransport::new())
+    }
+
+    #[test]
+    pub fn get_address_nonce(){
+        let address = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+        let client = mock_client();
+        client.transport().push_json(&json!({"data": {"nonce": 7}, "code": "successful"}));
+        let response = client.get_address_nonce(address).unwrap();
+        assert_eq!(response, 7);
+        let requests = client.transport().requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, Method::Get);
+        assert_eq!(requests[0].url, format!("https://api.elrond.com/address/{}/nonce", address));
+    }
+
+    #[test]
+    pub fn get_newly_generated_address_nonce() {
+        let account = Account::generate().unwrap();
+        let address = account.address.to_string();
+        let client = mock_client();
+        client.transport().push_json(&json!({"data": {"nonce": 0}, "code": "successful"}));
+        let nonce = client.get_address_nonce(&address).unwrap();
+        assert_eq!(nonce, 0);
+    }
+
+    #[test]
+    pub fn get_address_balance() {
+        let client = mock_client();
+        client.transport().push_json(&json!({"data": {"balance": "100000000000000"}, "code": "successful"}));
+        let address = "erd18gx50mf0xvz3c3xljm0s5pkz0zugsprltl86ux6f0sz94gqeua3q7l77wd";
+        let balance = client.get_address_balance(address).unwrap();
+        // since 0.2.0, `Display` gives the denominated form and `to_human` the eGLD value
+        assert_eq!(balance.to_string(), "100000000000000");
+        assert_eq!(balance.to_human().to_string(), "0.0001");
+    }
+
+    #[test]
+    pub fn post_signed_transaction() {
+        let private_key = "a4b36a5d97176618b5a7fcc9228d2fd98ee2f14ddd3d6462ae03e40eb487d15b";
+        let account = Account::from_string(private_key).unwrap();
+        let tx = UnsignedTransaction::new(
+            0,
+            "0.001",
+            "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k",
+            &account.address.to_string(),
+            Network::MainNet
+        ).unwrap();
+        let signed_tx = tx.sign(&account).unwrap();
+        let client = mock_client();
+        client.transport().push_json(&json!({"data": {"txHash": "abcd"}, "code": "successful"}));
+        let tx_hash = client.post_signed_transaction(signed_tx.clone()).unwrap();
+        assert_eq!(tx_hash, "abcd");
+        let request = &client.transport().requests()[0];
+        assert_eq!(request.method, Method::Post);
+        assert_eq!(request.url, "https://api.elrond.com/transaction/send");
+        let expected: serde_json::Value = serde_json::from_str(&signed_tx.serialize().unwrap()).unwrap();
+        assert_eq!(request.body.as_ref().unwrap(), &expected);
+    }
+
+    #[test]
+    pub fn error_responses_and_timeouts() {
+        let address = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+        let client = mock_client();
+        client.transport()
+            .push_response(500, "internal error")
+            .push_timeout()
+            .push_json(&json!({"data": {}}));
+        let err = client.get_address_nonce(address).unwrap_err();
+        assert_eq!(err.to_string(), "error code 500, data='internal error'");
+        let err = client.get_address_nonce(address).unwrap_err();
+        assert_eq!(err.to_string(), "request timed out");
+        let err = client.get_address_nonce(address).unwrap_err();
+        assert_eq!(err.to_string(), "response does not contain 'nonce' field");
+    }
+
+    fn retrying_client() -> Client<MockTransport> {
+        mock_client().with_retry_policy(
+            RetryPolicy::new(3).with_base_delay(Duration::from_millis(0)).with_jitter(false)
+        )
+    }
+
+    #[test]
+    pub fn retries_throttled_requests() {
+        let address = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+        let client = retrying_client();
+        client.transport()
+            .push_http_response(HttpResponse::new(429, "too many requests").with_header("Retry-After", "0"))
+            .push_timeout()
+            .push_json(&json!({"data": {"nonce": 3}}));
+        assert_eq!(client.get_address_nonce(address).unwrap(), 3);
+        assert_eq!(client.transport().requests().len(), 3);
+    }
+
+    #[test]
+    pub fn gives_up_after_max_attempts() {
+        let address = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+        let client = retrying_client();
+        client.transport()
+            .push_response(503, "unavailable")
+            .push_response(502, "bad gateway")
+            .push_response(500, "internal error")
+            .push_json(&json!({"data": {"nonce": 3}}));
+        let err = client.get_address_nonce(address).unwrap_err();
+        assert_eq!(err.to_string(), "error code 500, data='internal error'");
+        assert_eq!(client.transport().remaining(), 1);
+    }
+
+    #[test]
+    pub fn does_not_replay_unsafe_requests() {
+        let client = retrying_client();
+        client.transport()
+            .push_response(503, "unavailable")
+            .push_json(&json!({"data": {}}));
+        let request = ApiRequest::post("some/action".to_string(), json!({}));
+        assert!(client.request(&request).is_err());
+        assert_eq!(client.transport().requests().len(), 1);
+    }
+
+    #[test]
+    pub fn rate_limited_client_still_answers() {
+        let address = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+        let client = mock_client().with_rate_limiter(RateLimiter::new(10, Duration::from_secs(1)));
+        client.transport()
+            .push_json(&json!({"data": {"nonce": 1}}))
+            .push_json(&json!({"data": {"nonce": 2}}));
+        assert_eq!(client.get_address_nonce(address).unwrap(), 1);
+        assert_eq!(client.get_address_nonce(address).unwrap(), 2);
+    }
+
+    fn pooled_client() -> Client<MockTransport> {
+        let pool = EndpointPool::new(&["https://a.test", "https://b.test"]).unwrap().with_failure_threshold(1);
+        Client::with_endpoints(pool, MockTransport::new())
+    }
+
+    #[test]
+    pub fn fails_over_to_next_endpoint() {
+        let address = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+        let client = pooled_client();
+        client.transport()
+            .push_timeout()
+            .push_json(&json!({"data": {"nonce": 4}}))
+            .push_json(&json!({"data": {"nonce": 5}}));
+        assert_eq!(client.get_address_nonce(address).unwrap(), 4);
+        // the first endpoint's circuit is now open, so it is skipped
+        assert_eq!(client.get_address_nonce(address).unwrap(), 5);
+        let urls: Vec<String> = client.transport().requests().into_iter().map(|r| r.url).collect();
+        assert!(urls[0].starts_with("https://a.test/"));
+        assert!(urls[1].starts_with("https://b.test/"));
+        assert!(urls[2].starts_with("https://b.test/"));
+        let health = client.endpoints().health();
+        assert!(!health[0].healthy);
+        assert!(health[1].healthy);
+    }
+
+    #[test]
+    pub fn health_check_restores_endpoints() {
+        let client = pooled_client();
+        client.endpoints().record(0, true);
+        client.transport()
+            .push_json(&json!({"data": {"config": {}}}))
+            .push_response(503, "unavailable");
+        let health = client.check_health();
+        assert!(health[0].healthy);
+        assert!(!health[1].healthy);
+        assert!(client.transport().requests()[0].url.ends_with("/network/config"));
+    }
+
+    #[test]
+    pub fn consistency_check_compares_endpoints() {
+        let address = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+        let client = pooled_client();
+        client.transport()
+            .push_json(&json!({"data": {"nonce": 8}}))
+            .push_json(&json!({"data": {"nonce": 8}}))
+            .push_json(&json!({"data": {"nonce": 8}}))
+            .push_json(&json!({"data": {"nonce": 9}}));
+        assert_eq!(client.get_address_nonce_consistent(address).unwrap(), 8);
+        let err = client.get_address_nonce_consistent(address).unwrap_err();
+        assert!(err.to_string().starts_with("endpoints disagree"));
+    }
+
+    #[test]
+    pub fn consistency_check_skips_failed_endpoints() {
+        let address = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+        let client = Client::with_endpoints(
+            EndpointPool::new(&["https://a.test", "https://b.test"]).unwrap(),
+            MockTransport::new()
+        );
+        client.transport()
+            .push_timeout()
+            .push_json(&json!({"data": {"nonce": 8}}))
+            .push_timeout()
+            .push_timeout();
+        assert_eq!(client.get_address_nonce_consistent(address).unwrap(), 8);
+        assert!(client.get_address_nonce_consistent(address).is_err());
+    }
+
+    #[test]
+    pub fn throttling_does_not_open_circuit() {
+        let address = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+        let client = pooled_client();
+        client.transport()
+            .push_response(429, "too many requests")
+            .push_json(&json!({"data": {"nonce": 4}}));
+        // throttled requests still fail over to the next endpoint
+        assert_eq!(client.get_address_nonce(address).unwrap(), 4);
+        assert!(client.endpoints().health()[0].healthy);
+        assert_eq!(client.endpoints().health()[0].consecutive_failures, 0);
+    }
+
+    fn properties_response(decimals: u32) -> serde_json::Value {
+        let fields = vec![
+            base64::encode("Token"),
+            base64::encode("FungibleESDT"),
+            base64::encode([0u8; 32]),
+            base64::encode("1000"),
+            base64::encode("0"),
+            base64::encode(format!("NumDecimals-{}", decimals))
+        ];
+        json!({"data": {"data": {"returnCode": "ok", "returnData": fields}}})
+    }
+
+    #[test]
+    pub fn get_esdt_balance_applies_decimals() {
+        let address = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+        let client = mock_client();
+        client.transport()
+            .push_json(&properties_response(6))
+            .push_json(&json!({"data": {"tokenData": {"balance": "1234500", "tokenIdentifier": "USDC-c76f1f"}}}));
+        let balance = client.get_esdt_balance(address, "USDC-c76f1f").unwrap();
+        assert_eq!(balance.to_string(), "1.2345");
+        assert_eq!(balance.decimals(), 6);
+        let requests = client.transport().requests();
+        assert!(requests[0].url.ends_with("/vm-values/query"));
+        assert!(requests[1].url.ends_with("/esdt/USDC-c76f1f"));
+    }
+
+    #[test]
+    pub fn get_esdt_tokens_looks_up_each_collection() {
+        let address = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+        let client = mock_client();
+        client.transport()
+            .push_json(&json!({"data": {"esdts": {
+                "MEX-455c57": {"balance": "25000000000000000000", "tokenIdentifier": "MEX-455c57"},
+                "USDC-c76f1f": {"balance": "10", "tokenIdentifier": "USDC-c76f1f"}
+            }}}))
+            .push_json(&properties_response(18))
+            .push_json(&properties_response(6));
+        let tokens = client.get_esdt_tokens(address).unwrap();
+        let formatted: Vec<String> = tokens.iter().map(|t| format!("{} {}", t, t.identifier())).collect();
+        assert_eq!(formatted, vec!["25 MEX-455c57", "0.00001 USDC-c76f1f"]);
+    }
+
+    #[test]
+    pub fn get_transaction_status() {
+        let client = mock_client();
+        client.transport().push_json(&json!({"data": {"status": "success"}, "code": "successful"}));
+        assert_eq!(client.get_transaction_status("abcd").unwrap(), "success");
+        assert!(client.transport().requests()[0].url.ends_with("/transaction/abcd/status"));
+    }
+
+    #[test]
+    pub fn get_latest_block_hash_from_metachain() {
+        let client = mock_client();
+        client.transport()
+            .push_json(&json!({"data": {"status": {"erd_highest_final_nonce": 1200}}}))
+            .push_json(&json!({"data": {"hyperblock": {"hash": "abcd", "nonce": 1200, "timestamp": 1650000000}}}))
+            .push_json(&json!({"hash": "ef01", "nonce": 300, "shard": 1, "timestamp": 1650000006}));
+        assert_eq!(client.get_latest_block_hash().unwrap(), "abcd");
+        // shard blocks resolve too
+        assert_eq!(client.get_block_timestamp("ef01").unwrap(), 1650000006);
+        let requests = client.transport().requests();
+        assert!(requests[0].url.ends_with("/network/status/4294967295"));
+        assert!(requests[1].url.ends_with("/hyperblock/by-nonce/1200"));
+        assert!(requests[2].url.ends_with("/blocks/ef01"));
+    }
+
+    #[test]
+    pub fn get_delegation_amounts() {
+        let contract = "erd1qqqqqqqqqqqqqqqpqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqylllslmq6y6";
+        let delegator = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+        let client = mock_client();
+        client.transport()
+            .push_json(&json!({"data": {"data": {"returnCode": "ok", "returnData": [base64::encode([0x0d, 0xe0, 0xb6, 0xb3, 0xa7, 0x64, 0x00, 0x00])]}}}))
+            .push_json(&json!({"data": {"data": {"returnCode": "ok", "returnData": null}}}));
+        assert_eq!(client.get_delegated_stake(contract, delegator).unwrap().to_human().to_string(), "1");
+        assert_eq!(client.get_claimable_rewards(contract, delegator).unwrap().to_string(), "0");
+        let request = &client.transport().requests()[0];
+        let body = request.body.as_ref().unwrap();
+        assert_eq!(body["funcName"], "getUserActiveStake");
+        assert_eq!(body["args"][0], ElrondAddress::new(delegator).unwrap().to_hex());
+        assert!(client.get_undelegated_stake(contract, "erd1invalid").is_err());
+    }
+
+    #[test]
+    pub fn resolve_usernames() {
+        let address = "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k";
+        let bytes = ElrondAddress::new(address).unwrap().to_bytes();
+        let client = mock_client();
+        client.transport()
+            .push_json(&json!({"data": {"data": {"returnCode": "ok", "returnData": [base64::encode(bytes)]}}}))
+            .push_json(&json!({"data": {"data": {"returnCode": "ok", "returnData": [""]}}}))
+            .push_json(&json!({"data": {"username": "alice.elrond"}}))
+            .push_json(&json!({"data": {"username": ""}}));
+        assert_eq!(client.resolve_username("alice").unwrap().to_string(), address);
+        assert!(client.resolve_username("nobody").is_err());
+        assert_eq!(client.get_address_username(address).unwrap(), Some("alice.elrond".to_string()));
+        assert_eq!(client.get_address_username(address).unwrap(), None);
+        let requests = client.transport().requests();
+        let body = requests[0].body.as_ref().unwrap();
+        assert_eq!(body["funcName"], "resolve");
+        assert_eq!(body["args"][0], hex::encode("alice.elrond"));
+        assert!(requests[2].url.ends_with(&format!("/address/{}/username", address)));
+    }
+
+    fn hyperblock_response(nonce: u64) -> serde_json::Value {
+        json!({"data": {"hyperblock": {
+            "hash": format!("h{}", nonce), "nonce": nonce, "round": nonce, "epoch": 1, "timestamp": 1650000000 + nonce,
+            "transactions": [{
+                "type": "normal", "hash": "t1", "nonce": 1, "value": "1000000000000000000",
+                "sender": "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k",
+                "receiver": "erd146apxa83wr7paz3gsg07dhcpg98ascjtpg9p8l8g5rpmg6chhchq9ccvmc"
+            }]
+        }}})
+    }
+
+    #[test]
+    pub fn get_network_status_and_blocks() {
+        let client = mock_client();
+        client.transport()
+            .push_json(&json!({"data": {"status": {
+                "erd_current_round": 120, "erd_epoch_number": 3, "erd_nonce": 118, "erd_highest_final_nonce": 117
+            }}}))
+            .push_json(&json!({"data": {"block": {
+                "hash": "bb", "nonce": 118, "round": 120, "epoch": 3, "shard": 1,
+                "miniBlocks": [{"transactions": []}]
+            }}}))
+            .push_json(&hyperblock_response(40));
+        let status = client.get_network_status(1).unwrap();
+        assert_eq!((status.current_round, status.epoch, status.nonce, status.highest_final_nonce), (120, 3, 118, 117));
+        let block = client.get_block_by_nonce(1, 118).unwrap();
+        assert_eq!(block.shard, 1);
+        assert!(block.transactions.is_empty());
+        let hyperblock = client.get_hyperblock_by_nonce(40).unwrap();
+        assert_eq!(hyperblock.transactions[0].value.to_human().to_string(), "1");
+        let requests = client.transport().requests();
+        assert!(requests[0].url.ends_with("/network/status/1"));
+        assert!(requests[1].url.ends_with("/block/1/by-nonce/118?withTxs=true"));
+        assert!(requests[2].url.ends_with("/hyperblock/by-nonce/40"));
+    }
+
+    #[test]
+    pub fn final_hyperblocks_stop_at_final_nonce() {
+        let client = mock_client();
+        client.transport()
+            .push_json(&json!({"data": {"status": {"erd_highest_final_nonce": 41}}}))
+            .push_json(&hyperblock_response(40))
+            .push_json(&hyperblock_response(41))
+            .push_json(&json!({"data": {"status": {"erd_highest_final_nonce": 41}}}));
+        let mut hyperblocks = client.final_hyperblocks(40);
+        let nonces: Vec<u64> = hyperblocks.by_ref().map(|hyperblock| hyperblock.unwrap().nonce).collect();
+        assert_eq!(nonces, vec![40, 41]);
+        assert_eq!(hyperblocks.next_nonce(), 42);
+        assert_eq!(client.transport().requests().len(), 4);
+    }
+}
@@ -0,0 +1,250 @@
+//! Retry and rate limiting for requests to the Elrond network. The public API answers with 429s
+//! when it is overloaded and occasionally fails with 5xx errors, both of which usually go away
+//! after waiting a little.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use rand::Rng;
+use super::transport::HttpResponse;
+use super::super::Result;
+
+/// Configures how often and how long a client waits before retrying a failed request. Delays grow
+/// exponentially from `base_delay` up to `max_delay`. A `Retry-After` header sent by the server
+/// takes precedence, also capped at `max_delay`; only its number of seconds form is understood,
+/// HTTP dates being ignored in favour of the backoff. Only requests that are safe to replay are
+/// ever retried.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy that makes at most `max_attempts` attempts (including the first one),
+    /// starting with a 500ms backoff, capped at 30s, with jitter
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true
+        }
+    }
+    /// Policy that never retries, the default for clients
+    pub fn none() -> Self {
+        Self::new(1)
+    }
+    /// Set the delay before the first retry
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+    /// Set the maximum delay between two attempts, including delays asked for by `Retry-After`
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+    /// Enable or disable random jitter on backoff delays
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+    /// Maximum number of attempts made for a request
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+    /// Backoff delay after failed attempt number `attempt` (starting at 1), before jitter
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        self.base_delay
+            .checked_mul(1 << exponent)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+    /// Decide whether to retry after attempt number `attempt`, and if so how long to wait first
+    pub(crate) fn retry_delay(
+        &self,
+        attempt: u32,
+        replay_safe: bool,
+        outcome: &Result<HttpResponse>
+    ) -> Option<Duration> {
+        if !replay_safe || attempt >= self.max_attempts {
+            return None;
+        }
+        match outcome {
+            // no response at all, e.g., a timeout or refused connection
+            Err(_) => Some(self.jittered_backoff(attempt)),
+            Ok(response) if is_retryable_status(response.status) => {
+                match retry_after(response) {
+                    Some(delay) => Some(delay.min(self.max_delay)),
+                    None => Some(self.jittered_backoff(attempt))
+                }
+            },
+            Ok(_) => None
+        }
+    }
+    fn jittered_backoff(&self, attempt: u32) -> Duration {
+        let delay = self.backoff(attempt);
+        if self.jitter && delay > Duration::from_millis(0) {
+            // "equal jitter": keep half of the delay and randomize the other half
+            let half = delay / 2;
+            let extra = rand::thread_rng().gen_range(0, half.as_millis() as u64 + 1);
+            half + Duration::from_millis(extra)
+        } else {
+            delay
+        }
+    }
+}
+
+/// Whether the outcome of a request is worth trying again, on the same or another endpoint
+pub(crate) fn is_retryable(outcome: &Result<HttpResponse>) -> bool {
+    match outcome {
+        Err(_) => true,
+        Ok(response) => is_retryable_status(response.status)
+    }
+}
+
+/// Whether the outcome of a request means the endpoint failed to serve it. Throttling (429) is
+/// not a failure: the endpoint is up, and opening its circuit would only move the load elsewhere.
+pub(crate) fn is_failure(outcome: &Result<HttpResponse>) -> bool {
+    match outcome {
+        Err(_) => true,
+        Ok(response) => response.status != 429 && is_retryable_status(response.status)
+    }
+}
+
+/// Status codes worth retrying: rate limiting and transient server errors
+fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 429 | 500 | 502 | 503 | 504)
+}
+
+/// Parse the `Retry-After` header of a response, given as a number of seconds. The HTTP date
+/// form is not supported.
+fn retry_after(response: &HttpResponse) -> Option<Duration> {
+    response
+        .header("retry-after")
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// Client-side rate limiter allowing at most `max_requests` requests in any window of `per`
+#[derive(Debug)]
+pub struct RateLimiter {
+    max_requests: usize,
+    per: Duration,
+    sent: Mutex<VecDeque<Instant>>
+}
+
+impl RateLimiter {
+    /// Create a rate limiter allowing `max_requests` requests per `per` duration
+    pub fn new(max_requests: u32, per: Duration) -> Self {
+        Self {
+            max_requests: max_requests.max(1) as usize,
+            per,
+            sent: Mutex::new(VecDeque::new())
+        }
+    }
+    /// Reserve a slot for a new request, returning how long to wait before sending it
+    pub(crate) fn reserve(&self) -> Duration {
+        self.reserve_at(Instant::now())
+    }
+    fn reserve_at(&self, now: Instant) -> Duration {
+        let mut sent = self.sent.lock().expect("rate limiter lock poisoned");
+        while sent.len() >= self.max_requests {
+            // this unwrap is safe, the queue is not empty
+            let oldest = *sent.front().unwrap();
+            if now.duration_since(oldest) >= self.per {
+                sent.pop_front();
+            } else {
+                break;
+            }
+        }
+        let send_at = if sent.len() >= self.max_requests {
+            // wait until the oldest request in the window expires, taking its place
+            let oldest = sent.pop_front().unwrap();
+            oldest + self.per
+        } else {
+            now
+        };
+        sent.push_back(send_at);
+        send_at.saturating_duration_since(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use super::{RetryPolicy, RateLimiter};
+    use super::super::transport::HttpResponse;
+    use super::super::super::ElrondClientError;
+
+    #[test]
+    fn exponential_backoff_is_capped() {
+        let policy = RetryPolicy::new(10)
+            .with_base_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(500));
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+        assert_eq!(policy.backoff(40), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn retries_only_transient_failures() {
+        let policy = RetryPolicy::new(3).with_jitter(false).with_base_delay(Duration::from_millis(10));
+        let throttled = Ok(HttpResponse::new(429, "slow down"));
+        let unavailable = Ok(HttpResponse::new(503, ""));
+        let bad_request = Ok(HttpResponse::new(400, ""));
+        let timeout = Err(ElrondClientError::new("request timed out"));
+        assert_eq!(policy.retry_delay(1, true, &throttled), Some(Duration::from_millis(10)));
+        assert_eq!(policy.retry_delay(2, true, &unavailable), Some(Duration::from_millis(20)));
+        assert_eq!(policy.retry_delay(1, true, &timeout), Some(Duration::from_millis(10)));
+        assert_eq!(policy.retry_delay(1, true, &bad_request), None);
+        assert_eq!(policy.retry_delay(3, true, &throttled), None);
+        assert_eq!(policy.retry_delay(1, false, &throttled), None);
+    }
+
+    #[test]
+    fn honours_retry_after() {
+        let policy = RetryPolicy::new(3).with_max_delay(Duration::from_secs(10));
+        let throttled = Ok(HttpResponse::new(429, "").with_header("Retry-After", "5"));
+        assert_eq!(policy.retry_delay(1, true, &throttled), Some(Duration::from_secs(5)));
+        // servers can not block the client for longer than the maximum delay
+        let throttled = Ok(HttpResponse::new(429, "").with_header("Retry-After", "86400"));
+        assert_eq!(policy.retry_delay(1, true, &throttled), Some(Duration::from_secs(10)));
+        // HTTP dates fall back to the backoff
+        let policy = policy.with_jitter(false).with_base_delay(Duration::from_millis(10));
+        let dated = Ok(HttpResponse::new(503, "").with_header("Retry-After", "Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert_eq!(policy.retry_delay(1, true, &dated), Some(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let policy = RetryPolicy::new(3).with_base_delay(Duration::from_millis(100));
+        for _ in 0..100 {
+            let delay = policy.retry_delay(1, true, &Ok(HttpResponse::new(500, ""))).unwrap();
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn rate_limiter_spaces_requests() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(1));
+        let now = Instant::now();
+        assert_eq!(limiter.reserve_at(now), Duration::from_secs(0));
+        assert_eq!(limiter.reserve_at(now), Duration::from_secs(0));
+        assert_eq!(limiter.reserve_at(now), Duration::from_secs(1));
+        assert_eq!(limiter.reserve_at(now + Duration::from_millis(500)), Duration::from_millis(500));
+        assert_eq!(limiter.reserve_at(now + Duration::from_secs(3)), Duration::from_secs(0));
+    }
+}
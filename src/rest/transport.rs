@@ -0,0 +1,203 @@
+//! HTTP transports used by `Client`. A transport only moves a request to the network and hands
+//! back the raw response, so custom HTTP stacks (or scripted responses in tests) can be swapped in.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+use serde_json::Value;
+use super::super::{Result, ElrondClientError};
+
+/// HTTP method of an outgoing request
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    Get,
+    Post
+}
+
+/// Outgoing HTTP request, with a JSON body if any
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub body: Option<Value>
+}
+
+/// Raw HTTP response as returned by a transport
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String
+}
+
+impl HttpResponse {
+    /// Create a new response from a status code and body
+    pub fn new(status: u16, body: &str) -> Self {
+        Self { status, headers: Vec::new(), body: body.to_string() }
+    }
+    /// Add a header to the response
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+    /// Get the value of a header, ignoring case of the name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A transport sends HTTP requests on behalf of a `Client`. Failures to get any response at all
+/// (DNS, connection errors, timeouts) are returned as errors, whereas any response from the server,
+/// whatever its status code, is returned as an `HttpResponse`.
+pub trait Transport {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse>;
+}
+
+/// Default transport, backed by blocking `ureq` requests
+#[derive(Clone, Debug, Default)]
+pub struct UreqTransport {
+    timeout: Option<Duration>
+}
+
+impl UreqTransport {
+    /// Create a new transport without a timeout
+    pub fn new() -> Self {
+        Self { timeout: None }
+    }
+    /// Create a new transport that gives up on requests after `timeout`
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self { timeout: Some(timeout) }
+    }
+}
+
+impl Transport for UreqTransport {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse> {
+        let mut req = match request.method {
+            Method::Get => ureq::get(&request.url),
+            Method::Post => ureq::post(&request.url)
+        };
+        if let Some(timeout) = self.timeout {
+            req.timeout(timeout);
+        }
+        let resp = match &request.body {
+            Some(json) => req.send_json(json.clone()),
+            None => req.call()
+        };
+        // ureq reports transport failures as synthetic responses
+        if let Some(err) = resp.synthetic_error() {
+            return Err(ElrondClientError::new(&format!("request failed: {}", err)));
+        }
+        let status = resp.status();
+        let headers = resp
+            .headers_names()
+            .into_iter()
+            .filter_map(|name| {
+                let value = resp.header(&name)?.to_string();
+                Some((name, value))
+            })
+            .collect();
+        let body = resp.into_string().map_err(|_|{
+            ElrondClientError::new("could not decode response to string")
+        })?;
+        Ok(HttpResponse { status, headers, body })
+    }
+}
+
+/// Scripted outcome of a request sent to a `MockTransport`
+#[derive(Clone, Debug)]
+enum MockOutcome {
+    Response(HttpResponse),
+    Error(String)
+}
+
+/// In-memory transport for tests. Responses are scripted ahead of time and handed out in order,
+/// and every request is recorded so it can be inspected afterwards.
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    outcomes: Mutex<VecDeque<MockOutcome>>,
+    requests: Mutex<Vec<HttpRequest>>
+}
+
+impl MockTransport {
+    /// Create a new transport with no scripted responses
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Script the next response
+    pub fn push_response(&self, status: u16, body: &str) -> &Self {
+        self.push(MockOutcome::Response(HttpResponse::new(status, body)))
+    }
+    /// Script a complete response, e.g., one with headers
+    pub fn push_http_response(&self, response: HttpResponse) -> &Self {
+        self.push(MockOutcome::Response(response))
+    }
+    /// Script a successful response with a JSON body
+    pub fn push_json(&self, body: &Value) -> &Self {
+        self.push_response(200, &body.to_string())
+    }
+    /// Script a transport failure, e.g., a refused connection
+    pub fn push_error(&self, message: &str) -> &Self {
+        self.push(MockOutcome::Error(message.to_string()))
+    }
+    /// Script a request that times out
+    pub fn push_timeout(&self) -> &Self {
+        self.push_error("request timed out")
+    }
+    /// All requests sent through the transport so far, oldest first
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().expect("mock transport lock poisoned").clone()
+    }
+    /// Number of scripted outcomes that have not been used yet
+    pub fn remaining(&self) -> usize {
+        self.outcomes.lock().expect("mock transport lock poisoned").len()
+    }
+    fn push(&self, outcome: MockOutcome) -> &Self {
+        self.outcomes.lock().expect("mock transport lock poisoned").push_back(outcome);
+        self
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse> {
+        self.requests.lock().expect("mock transport lock poisoned").push(request.clone());
+        let outcome = self.outcomes.lock().expect("mock transport lock poisoned").pop_front();
+        match outcome {
+            Some(MockOutcome::Response(response)) => Ok(response),
+            Some(MockOutcome::Error(message)) => Err(ElrondClientError::new(&message)),
+            None => Err(ElrondClientError::new(
+                &format!("no scripted response for request to '{}'", request.url)
+            ))
+        }
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for &T {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse> {
+        (**self).send(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MockTransport, Transport, HttpRequest, HttpResponse, Method};
+
+    fn get(url: &str) -> HttpRequest {
+        HttpRequest { method: Method::Get, url: url.to_string(), body: None }
+    }
+
+    #[test]
+    fn mock_replays_script_in_order() {
+        let mock = MockTransport::new();
+        mock.push_response(200, "first").push_timeout().push_response(500, "third");
+        assert_eq!(mock.send(&get("a")).unwrap(), HttpResponse::new(200, "first"));
+        assert_eq!(mock.send(&get("b")).unwrap_err().to_string(), "request timed out");
+        assert_eq!(mock.send(&get("c")).unwrap(), HttpResponse::new(500, "third"));
+        assert!(mock.send(&get("d")).is_err());
+        let urls: Vec<String> = mock.requests().into_iter().map(|r| r.url).collect();
+        assert_eq!(urls, vec!["a", "b", "c", "d"]);
+        assert_eq!(mock.remaining(), 0);
+    }
+}
@@ -0,0 +1,127 @@
+//! Signers abstract over where secret keys live. Transactions can be signed by a local `Account`,
+//! by a `RemoteSigner` asking an HTTP signing service, or by any other implementation of `Signer`
+//! (e.g., backed by a keystore or aggregating several parties) without exposing the secret key.
+
+use serde_json::json;
+use super::message::verify_signature;
+use super::{ElrondAddress, Transport, UreqTransport, HttpRequest, Method, Result, ElrondClientError};
+
+/// Something able to sign data on behalf of an Elrond address
+pub trait Signer {
+    /// Address the signatures can be verified against
+    fn address(&self) -> ElrondAddress;
+    /// Sign raw bytes and return the 64 byte ed25519 signature
+    fn sign_bytes(&self, data: &[u8]) -> Result<Vec<u8>>;
+}
+
+impl<S: Signer + ?Sized> Signer for &S {
+    fn address(&self) -> ElrondAddress {
+        (**self).address()
+    }
+    fn sign_bytes(&self, data: &[u8]) -> Result<Vec<u8>> {
+        (**self).sign_bytes(data)
+    }
+}
+
+/// Signer delegating to an HTTP signing service. The service receives a POST request with body
+/// `{"address": "erd1...", "data": "<hex>"}` and answers `{"signature": "<hex>"}`. Signatures are
+/// verified against the address before being returned.
+pub struct RemoteSigner<T: Transport = UreqTransport> {
+    url: String,
+    address: ElrondAddress,
+    transport: T
+}
+
+impl RemoteSigner {
+    /// Create a new signer for `address`, sending requests to the signing service at `url`
+    pub fn new(url: &str, address: ElrondAddress) -> Self {
+        Self::with_transport(url, address, UreqTransport::new())
+    }
+}
+
+impl<T: Transport> RemoteSigner<T> {
+    /// Create a new signer sending its requests through a custom transport
+    pub fn with_transport(url: &str, address: ElrondAddress, transport: T) -> Self {
+        Self { url: url.to_string(), address, transport }
+    }
+
+    /// Get the transport used by the signer
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: Transport> Signer for RemoteSigner<T> {
+    fn address(&self) -> ElrondAddress {
+        self.address.clone()
+    }
+
+    fn sign_bytes(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let request = HttpRequest {
+            method: Method::Post,
+            url: self.url.clone(),
+            body: Some(json!({ "address": self.address.to_string(), "data": hex::encode(data) }))
+        };
+        let response = self.transport.send(&request)?;
+        if response.status != 200 {
+            return Err(ElrondClientError::new(&format!(
+                "signing service returned status {}", response.status
+            )));
+        }
+        let body: serde_json::Value = serde_json::from_str(&response.body).map_err(|_| {
+            ElrondClientError::new("could not parse signing service response")
+        })?;
+        let signature = body
+            .get("signature")
+            .and_then(|signature| signature.as_str())
+            .ok_or(ElrondClientError::new("signing service response does not contain a 'signature'"))?;
+        verify_signature(&self.address, data, signature).map_err(|e| {
+            ElrondClientError::new(&format!("signing service returned an invalid signature: {}", e))
+        })?;
+        // this unwrap is safe, the signature was checked to be hex encoded
+        Ok(hex::decode(signature).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::{Signer, RemoteSigner};
+    use super::super::{Account, MockTransport, UnsignedTransaction, Network};
+
+    fn account() -> Account {
+        Account::from_string("a4b36a5d97176618b5a7fcc9228d2fd98ee2f14ddd3d6462ae03e40eb487d15b").unwrap()
+    }
+
+    #[test]
+    fn remote_signer_signs_like_account() {
+        let account = account();
+        let remote = RemoteSigner::with_transport(
+            "https://signer.local/sign", account.address.clone(), MockTransport::new()
+        );
+        let tx = UnsignedTransaction::new(
+            0,
+            "0.001",
+            "erd16jats393r8rnut88yhvu5wvxxje57qzlj3tqk7n6jnf7f6cxs4uqfeh65k",
+            &account.address.to_string(),
+            Network::MainNet
+        ).unwrap();
+        let expected = account.sign_bytes(tx.serialize().unwrap().as_bytes()).unwrap();
+        remote.transport().push_json(&json!({ "signature": hex::encode(&expected) }));
+        let signed = tx.sign(&remote).unwrap();
+        assert_eq!(signed.signature(), hex::encode(&expected));
+        let request = &remote.transport().requests()[0];
+        assert_eq!(request.body.as_ref().unwrap()["address"], account.address.to_string());
+    }
+
+    #[test]
+    fn remote_signer_rejects_bad_signatures() {
+        let remote = RemoteSigner::with_transport(
+            "https://signer.local/sign", account().address.clone(), MockTransport::new()
+        );
+        remote.transport().push_json(&json!({ "signature": hex::encode([7u8; 64]) }));
+        assert!(remote.sign_bytes(b"data").is_err());
+        remote.transport().push_response(403, "forbidden");
+        assert!(remote.sign_bytes(b"data").is_err());
+    }
+}
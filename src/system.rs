@@ -0,0 +1,189 @@
+//! System smart contracts run on the metachain at fixed addresses (the `*_SC` constants of
+//! `ElrondAddress`). They are called with transactions whose data is the function name followed by
+//! its hex encoded arguments, separated by '@'. This module builds the most used calls to the
+//! validator and governance contracts.
+
+use std::str::FromStr;
+use num_bigint::BigUint;
+use super::transaction::{MIN_GAS_LIMIT, GAS_PER_DATA_BYTE};
+use super::{Network, UnsignedTransaction, ElrondAddress, ElrondCurrencyAmount, ElrondClientError, Result};
+
+/// Gas consumed by calls to system smart contracts, on top of the cost of their data. Validator
+/// calls consume it once for each node (BLS key) they handle, as estimated by erdpy.
+pub(crate) const SYSTEM_CALL_GAS: u64 = 5000000;
+/// Fee paid for unjailing a node, in denominated eGLD (2.5 eGLD)
+const UNJAIL_FEE: u128 = 2500000000000000000;
+/// Length in bytes of the BLS public key of a node
+const BLS_KEY_LENGTH: usize = 96;
+
+/// Encode an integer argument as the VM does: big-endian, without leading zero bytes
+pub(crate) fn u64_arg(value: u64) -> Vec<u8> {
+    value.to_be_bytes().iter().skip_while(|byte| **byte == 0).cloned().collect()
+}
+
+/// Encode an integer argument given as decimal digits, e.g., an amount in base units
+pub(crate) fn biguint_arg(digits: &str) -> Result<Vec<u8>> {
+    let value = BigUint::from_str(digits).map_err(|_| {
+        ElrondClientError::new(&format!("'{}' is not an unsigned integer", digits))
+    })?;
+    Ok(value.to_bytes_be().into_iter().skip_while(|byte| *byte == 0).collect())
+}
+
+/// Data field of a smart contract call, "function@arg1@arg2..." with hex encoded arguments
+pub(crate) fn call_data(function: &str, args: &[Vec<u8>]) -> String {
+    args.iter().fold(function.to_string(), |data, arg| data + "@" + &hex::encode(arg))
+}
+
+/// Internal helper for building a call from `sender` to `contract`, with `gas` on top of the cost
+/// of the data
+pub(crate) fn contract_call(
+    sender: &str,
+    nonce: u64,
+    contract: &ElrondAddress,
+    value: &str,
+    data: &str,
+    gas: u64,
+    network: Network
+) -> Result<UnsignedTransaction> {
+    let gas_limit = MIN_GAS_LIMIT + GAS_PER_DATA_BYTE * data.len() as u64 + gas;
+    Ok(UnsignedTransaction::new(nonce, value, &contract.to_string(), sender, network)?
+        .with_data(data.as_bytes())
+        .with_gas_limit(gas_limit))
+}
+
+/// Decode the hex encoded BLS public keys of nodes
+fn bls_keys(keys: &[&str]) -> Result<Vec<Vec<u8>>> {
+    if keys.is_empty() {
+        return Err(ElrondClientError::new("at least one BLS key is required"));
+    }
+    keys.iter()
+        .map(|key| match hex::decode(key) {
+            Ok(bytes) if bytes.len() == BLS_KEY_LENGTH => Ok(bytes),
+            _ => Err(ElrondClientError::new(&format!("'{}' is not a hex encoded BLS key", key)))
+        })
+        .collect()
+}
+
+/// Internal helper for validator calls taking a list of BLS keys
+fn node_operation(owner: &str, nonce: u64, function: &str, keys: &[&str], value: &str, network: Network) -> Result<UnsignedTransaction> {
+    let keys = bls_keys(keys)?;
+    let gas = SYSTEM_CALL_GAS * keys.len() as u64;
+    contract_call(owner, nonce, &ElrondAddress::VALIDATOR_SC, value, &call_data(function, &keys), gas, network)
+}
+
+/// Stake `value` eGLD for new nodes, given as pairs of hex encoded BLS key and signature of the
+/// owner address by that key. The value must cover the node price (2500 eGLD per node on mainnet).
+pub fn stake(owner: &str, nonce: u64, value: &str, nodes: &[(&str, &str)], network: Network) -> Result<UnsignedTransaction> {
+    let keys: Vec<&str> = nodes.iter().map(|(key, _)| *key).collect();
+    let keys = bls_keys(&keys)?;
+    let mut args = vec![u64_arg(nodes.len() as u64)];
+    for (key, (_, signature)) in keys.into_iter().zip(nodes) {
+        let signature = hex::decode(signature).map_err(|_| {
+            ElrondClientError::new(&format!("'{}' is not a hex encoded signature", signature))
+        })?;
+        args.push(key);
+        args.push(signature);
+    }
+    let gas = SYSTEM_CALL_GAS * nodes.len() as u64;
+    contract_call(owner, nonce, &ElrondAddress::VALIDATOR_SC, value, &call_data("stake", &args), gas, network)
+}
+
+/// Stop validating with nodes, whose stake can be withdrawn with `unbond` after the unbonding period
+pub fn unstake(owner: &str, nonce: u64, keys: &[&str], network: Network) -> Result<UnsignedTransaction> {
+    node_operation(owner, nonce, "unStake", keys, "0", network)
+}
+
+/// Withdraw the stake of unstaked nodes
+pub fn unbond(owner: &str, nonce: u64, keys: &[&str], network: Network) -> Result<UnsignedTransaction> {
+    node_operation(owner, nonce, "unBond", keys, "0", network)
+}
+
+/// Unjail nodes, paying the 2.5 eGLD fee of each node
+pub fn unjail(owner: &str, nonce: u64, keys: &[&str], network: Network) -> Result<UnsignedTransaction> {
+    let fee = ElrondCurrencyAmount::from_denominated(&(UNJAIL_FEE * keys.len() as u128).to_string())?;
+    node_operation(owner, nonce, "unJail", keys, &fee.to_human().to_string(), network)
+}
+
+/// Claim the stake exceeding what the nodes of `owner` need
+pub fn claim(owner: &str, nonce: u64, network: Network) -> Result<UnsignedTransaction> {
+    contract_call(owner, nonce, &ElrondAddress::VALIDATOR_SC, "0", "claim", SYSTEM_CALL_GAS, network)
+}
+
+/// Vote cast on a governance proposal
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Vote {
+    Yes,
+    No,
+    Abstain,
+    /// Against the proposal, asking for the proposer to lose their deposit
+    Veto
+}
+
+impl Vote {
+    /// Name of the vote, as expected by the governance contract
+    fn as_str(&self) -> &'static str {
+        match self {
+            Vote::Yes => "yes",
+            Vote::No => "no",
+            Vote::Abstain => "abstain",
+            Vote::Veto => "veto"
+        }
+    }
+}
+
+/// Vote on the governance proposal with the given nonce
+pub fn vote(voter: &str, nonce: u64, proposal: u64, vote: Vote, network: Network) -> Result<UnsignedTransaction> {
+    let data = call_data("vote", &[u64_arg(proposal), vote.as_str().as_bytes().to_vec()]);
+    contract_call(voter, nonce, &ElrondAddress::GOVERNANCE_SC, "0", &data, SYSTEM_CALL_GAS, network)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{stake, unjail, unbond, claim, vote, u64_arg, biguint_arg, Vote};
+    use super::super::{ElrondAddress, Network};
+
+    const OWNER: &str = "erd146apxa83wr7paz3gsg07dhcpg98ascjtpg9p8l8g5rpmg6chhchq9ccvmc";
+
+    fn key(byte: u8) -> String {
+        hex::encode([byte; 96])
+    }
+
+    #[test]
+    fn integer_arguments() {
+        assert_eq!(u64_arg(0), Vec::<u8>::new());
+        assert_eq!(u64_arg(1), vec![1]);
+        assert_eq!(u64_arg(0x0100), vec![1, 0]);
+        assert_eq!(biguint_arg("0").unwrap(), Vec::<u8>::new());
+        assert_eq!(biguint_arg("1000000000000000000").unwrap(), hex::decode("0de0b6b3a7640000").unwrap());
+        assert!(biguint_arg("-1").is_err());
+    }
+
+    #[test]
+    fn validator_calls() {
+        let (key1, key2) = (key(1), key(2));
+        let tx = stake(OWNER, 1, "5000", &[(&key1, "aa"), (&key2, "bb")], Network::MainNet).unwrap();
+        let data = String::from_utf8(tx.data().unwrap()).unwrap();
+        assert_eq!(data, format!("stake@02@{}@aa@{}@bb", key1, key2));
+        assert_eq!(tx.receiver(), ElrondAddress::VALIDATOR_SC.to_string());
+        assert_eq!(tx.value().unwrap().to_human().to_string(), "5000");
+        // gas limits as estimated by erdpy for the same calls
+        assert_eq!(tx.gas_limit(), 10650000);
+        let tx = unjail(OWNER, 2, &[&key1, &key2], Network::MainNet).unwrap();
+        assert_eq!(tx.value().unwrap().to_human().to_string(), "5");
+        assert_eq!(tx.gas_limit(), 10638000);
+        let tx = unbond(OWNER, 3, &[&key1], Network::MainNet).unwrap();
+        assert_eq!(tx.data().unwrap(), format!("unBond@{}", key1).as_bytes());
+        assert_eq!(tx.gas_limit(), 5348500);
+        assert!(unbond(OWNER, 3, &["abcd"], Network::MainNet).is_err());
+        assert!(unbond(OWNER, 3, &[], Network::MainNet).is_err());
+        assert_eq!(claim(OWNER, 4, Network::MainNet).unwrap().gas_limit(), 5057500);
+    }
+
+    #[test]
+    fn governance_vote() {
+        let tx = vote(OWNER, 1, 12, Vote::Abstain, Network::MainNet).unwrap();
+        assert_eq!(tx.data().unwrap(), b"vote@0c@6162737461696e");
+        assert_eq!(tx.receiver(), ElrondAddress::GOVERNANCE_SC.to_string());
+        assert_eq!(tx.value().unwrap().to_string(), "0");
+    }
+}
@@ -1,9 +1,9 @@
 //! Logic for constructing transactions on the Elrond network.
 
-use serde::Serialize;
-use super::{Account, ElrondAddress, ElrondClientError, Result};
-use bigdecimal::BigDecimal;
-use std::str::FromStr;
+use serde::{Serialize, Deserialize};
+use sha3::{Digest, Keccak256};
+use super::message::verify_signature;
+use super::{Signer, ElrondAddress, ElrondCurrencyAmount, ElrondClientError, Result};
 
 /// Network representation (e.g., MainNet or TestNet)
 pub enum Network {
@@ -21,52 +21,80 @@ impl Network {
     }
 }
 
-/// eGLD representation. "1 eGLD" is 10^18 on the blockchain
-#[derive(Clone, Debug, PartialEq)]
-pub struct ElrondCurrencyAmount {
-    inner: String
+/// Gas limit of a transaction moving eGLD without data
+pub(crate) const MIN_GAS_LIMIT: u64 = 50000;
+/// Additional gas consumed by each byte of the data field
+pub(crate) const GAS_PER_DATA_BYTE: u64 = 1500;
+
+/// Additional gas consumed by guarded transactions, for checking the guardian signature
+pub(crate) const GUARDED_TX_EXTRA_GAS: u64 = 50000;
+/// Options bit marking a transaction as signed over the keccak256 hash of its JSON, as done by
+/// hardware wallets, rather than over the JSON itself
+pub const OPTION_HASH_SIGN: u32 = 0b01;
+/// Options bit marking a transaction as guarded, co-signed by the guardian of the sender
+pub const OPTION_GUARDED: u32 = 0b10;
+
+/// Check the signature of a guardian or relayer, which must be present exactly when its address is
+fn verify_co_signature(role: &str, address: &Option<String>, signature: &Option<String>, message: &[u8]) -> Result<()> {
+    match (address, signature) {
+        (Some(address), Some(signature)) => verify_signature(&ElrondAddress::new(address)?, message, signature),
+        (Some(_), None) => Err(ElrondClientError::new(&format!("transaction is missing the {} signature", role))),
+        (None, Some(_)) => Err(ElrondClientError::new(&format!("transaction has a {} signature but no {}", role, role))),
+        (None, None) => Ok(())
+    }
 }
 
-impl ElrondCurrencyAmount {
-    /// Create an amount of eGLD from a human input, e.g., "2 eGLD"
-    pub fn new(amount: &str) -> Result<Self> {
-        let amount = BigDecimal::from_str(amount).map_err(|_| {
-            ElrondClientError::new("could not parse amount as bigdecimal")
-        })?;
-        let multiplier = BigDecimal::from_str("1000000000000000000").unwrap(); // safe
-        let converted_amount = (amount * multiplier).with_scale(0);
-        let inner = format!("{}", converted_amount);
-        Ok(Self { inner })
-    }
-    /// Parse blockchain representation of eGLD into human readable form
-    pub fn from_blockchain_precision(blockchain_amount: &str) -> Result<Self> {
-        let amount = BigDecimal::from_str(blockchain_amount).map_err(|_| {
-            ElrondClientError::new("could not parse amount as bigdecimal")
-        })?;
-        let divisor = BigDecimal::from_str("1000000000000000000").unwrap(); // safe
-        let converted_amount = amount / divisor;
-        let inner = format!("{}", converted_amount);
-        Ok(Self { inner })
-    }
-    /// Convert to string for serialization
-    pub fn to_string(&self) -> String {
-        self.inner.clone()
+/// Check that a deserialized address is a canonical bech32 "erd" address, as produced by `new`
+fn check_address(field: &str, address: &str) -> Result<()> {
+    if ElrondAddress::new(address)?.to_string() != address {
+        return Err(ElrondClientError::new(&format!("{} '{}' is not a canonical address", field, address)));
     }
+    Ok(())
 }
 
 /// Transaction representation before it has been signed by an account
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")] 
 pub struct UnsignedTransaction{
     nonce: u64,
     value: String,
     receiver: String,
     sender: String,
+    // base64 encoded usernames (herotags), omitted from the signed JSON when empty
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    sender_username: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    receiver_username: String,
     gas_price: u64,
     gas_limit: u64,
-    // 'chain_ID' needs to be weirdly cased due to requirements of Elrond API
-    chain_ID: String,
-    version: u64
+    // base64 encoded, omitted from the signed JSON when empty
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    data: String,
+    // 'chainID' needs to be weirdly cased due to requirements of Elrond API
+    #[serde(rename = "chainID")]
+    chain_id: String,
+    version: u64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    options: u32,
+    // guardian co-signing the transaction of a guarded account
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    guardian: Option<String>,
+    // relayer paying the gas of a relayed (v3) transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    relayer: Option<String>
+}
+
+/// Decode a base64 encoded username
+fn decode_username(username: &str) -> Result<String> {
+    base64::decode(username)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| ElrondClientError::new("username is not valid base64 encoded UTF-8"))
+}
+
+/// Whether options are unset, in which case they are omitted from the JSON
+fn is_zero(options: &u32) -> bool {
+    *options == 0
 }
 
 impl UnsignedTransaction {
@@ -83,57 +111,330 @@ impl UnsignedTransaction {
             value: ElrondCurrencyAmount::new(value)?.to_string(),
             receiver: ElrondAddress::new(receiver)?.to_string(),
             sender: ElrondAddress::new(sender)?.to_string(),
+            sender_username: String::new(),
+            receiver_username: String::new(),
             gas_price: 1000000000,
-            gas_limit: 50000,
-            chain_ID: network.chain_id(),
-            version: 1
+            gas_limit: MIN_GAS_LIMIT,
+            data: String::new(),
+            chain_id: network.chain_id(),
+            version: 1,
+            options: 0,
+            guardian: None,
+            relayer: None
         })
     }
+    /// Attach data to the transaction, e.g., a smart contract call. The cost of moving the data
+    /// is added to the gas limit (replacing the cost of any previous data), use `with_gas_limit`
+    /// afterwards if the call needs more.
+    pub fn with_data(mut self, data: &[u8]) -> Self {
+        let previous = self.data().map(|previous| previous.len()).unwrap_or(0) as u64;
+        self.gas_limit = self.gas_limit.saturating_sub(GAS_PER_DATA_BYTE * previous)
+            + GAS_PER_DATA_BYTE * data.len() as u64;
+        self.data = base64::encode(data);
+        self
+    }
+    /// Set the maximum amount of gas the transaction may consume
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+    /// Set the price paid per unit of gas, in denominated eGLD
+    pub fn with_gas_price(mut self, gas_price: u64) -> Self {
+        self.gas_price = gas_price;
+        self
+    }
+    /// Set the version of the transaction format. Options require version 2 or higher.
+    pub fn with_version(mut self, version: u64) -> Self {
+        self.version = version;
+        self
+    }
+    /// Set the options of the transaction, a set of `OPTION_*` bits. Prefer the dedicated
+    /// builders (`with_hash_signing`, `with_guardian`), which also set the version.
+    pub fn with_options(mut self, options: u32) -> Self {
+        self.options = options;
+        self
+    }
+    /// Sign the keccak256 hash of the transaction rather than its JSON (version 2 with the hash
+    /// signing option), as required by some hardware wallets
+    pub fn with_hash_signing(mut self) -> Self {
+        self.version = self.version.max(2);
+        self.options |= OPTION_HASH_SIGN;
+        self
+    }
+    /// Set the username (herotag) of the sender, checked by the protocol against the sender
+    pub fn with_sender_username(mut self, username: &str) -> Self {
+        self.sender_username = base64::encode(username);
+        self
+    }
+    /// Set the username (herotag) of the receiver, checked by the protocol against the receiver
+    pub fn with_receiver_username(mut self, username: &str) -> Self {
+        self.receiver_username = base64::encode(username);
+        self
+    }
+    /// Have the transaction co-signed by `guardian`, the guardian of the sender. This marks the
+    /// transaction as guarded (version 2 with the guarded option) and adds the gas needed to check
+    /// the guardian signature, so set it after `with_gas_limit`.
+    pub fn with_guardian(mut self, guardian: &str) -> Result<Self> {
+        self.guardian = Some(ElrondAddress::new(guardian)?.to_string());
+        self.version = self.version.max(2);
+        self.options |= OPTION_GUARDED;
+        self.gas_limit += GUARDED_TX_EXTRA_GAS;
+        Ok(self)
+    }
+    /// Have `relayer` pay the gas of the transaction (relayed v3). Both the sender and the relayer
+    /// sign the transaction, which costs an extra `MIN_GAS_LIMIT`. Set the relayer after
+    /// `with_gas_limit`, as it adds to the gas limit.
+    pub fn with_relayer(mut self, relayer: &str) -> Result<Self> {
+        self.relayer = Some(ElrondAddress::new(relayer)?.to_string());
+        self.gas_limit += MIN_GAS_LIMIT;
+        Ok(self)
+    }
+    /// Check the fields that `new` and the builders validate, for a transaction that was
+    /// deserialized rather than built, e.g., imported on an offline machine
+    pub fn validate(&self) -> Result<()> {
+        ElrondCurrencyAmount::from_denominated(&self.value)?;
+        if self.value.len() > 1 && self.value.starts_with('0') {
+            return Err(ElrondClientError::new(&format!("value '{}' has leading zeros", self.value)));
+        }
+        check_address("sender", &self.sender)?;
+        check_address("receiver", &self.receiver)?;
+        if let Some(guardian) = &self.guardian {
+            check_address("guardian", guardian)?;
+        }
+        if let Some(relayer) = &self.relayer {
+            check_address("relayer", relayer)?;
+        }
+        self.sender_username()?;
+        self.receiver_username()?;
+        self.data()?;
+        Ok(())
+    }
     /// Serialize transaction for signing
     pub fn serialize(&self) -> Result<String> {
         serde_json::to_string(self).map_err(|_| {
             ElrondClientError::new("could not serialize unsigned transaction")
         })
     }
-    /// Sign the transaction with an `Account` to produce a `SignedTransaction`
-    pub fn sign(&self, account: &Account) -> Result<SignedTransaction> {
+    /// Bytes signed by the sender (and guardian or relayer): the serialized transaction, or its
+    /// keccak256 hash with the hash signing option
+    pub fn signing_bytes(&self) -> Result<Vec<u8>> {
+        if self.options != 0 && self.version < 2 {
+            return Err(ElrondClientError::new("transaction options require version 2"));
+        }
         let serialized_tx = self.serialize()?;
-        let signature = account.sign(&serialized_tx)?;
+        if self.options & OPTION_HASH_SIGN != 0 {
+            Ok(Keccak256::digest(serialized_tx.as_bytes()).to_vec())
+        } else {
+            Ok(serialized_tx.into_bytes())
+        }
+    }
+    /// Nonce of the sender the transaction will be executed at
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+    /// Amount of eGLD transferred
+    pub fn value(&self) -> Result<ElrondCurrencyAmount> {
+        ElrondCurrencyAmount::from_denominated(&self.value)
+    }
+    /// bech32 address of the receiver
+    pub fn receiver(&self) -> &str {
+        &self.receiver
+    }
+    /// bech32 address of the sender
+    pub fn sender(&self) -> &str {
+        &self.sender
+    }
+    /// Username (herotag) of the sender, empty if not set
+    pub fn sender_username(&self) -> Result<String> {
+        decode_username(&self.sender_username)
+    }
+    /// Username (herotag) of the receiver, empty if not set
+    pub fn receiver_username(&self) -> Result<String> {
+        decode_username(&self.receiver_username)
+    }
+    /// Price paid per unit of gas, in denominated eGLD
+    pub fn gas_price(&self) -> u64 {
+        self.gas_price
+    }
+    /// Maximum amount of gas the transaction may consume
+    pub fn gas_limit(&self) -> u64 {
+        self.gas_limit
+    }
+    /// Data attached to the transaction
+    pub fn data(&self) -> Result<Vec<u8>> {
+        base64::decode(&self.data).map_err(|_| {
+            ElrondClientError::new("transaction data is not valid base64")
+        })
+    }
+    /// Options of the transaction, a set of `OPTION_*` bits
+    pub fn options(&self) -> u32 {
+        self.options
+    }
+    /// bech32 address of the guardian co-signing the transaction, if any
+    pub fn guardian(&self) -> Option<&str> {
+        self.guardian.as_deref()
+    }
+    /// bech32 address of the relayer paying the gas, if any
+    pub fn relayer(&self) -> Option<&str> {
+        self.relayer.as_deref()
+    }
+    /// Version of the transaction format
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+    /// Chain id of the network the transaction is valid on
+    pub fn chain_id(&self) -> &str {
+        &self.chain_id
+    }
+    /// Highest fee the transaction can cost, when all of its gas is consumed
+    pub fn max_fee(&self) -> Result<ElrondCurrencyAmount> {
+        let fee = self.gas_price as u128 * self.gas_limit as u128;
+        ElrondCurrencyAmount::from_denominated(&fee.to_string())
+    }
+    /// Human-readable summary of the transaction, to review before signing or broadcasting
+    pub fn summary(&self) -> Result<String> {
+        Ok(format!(
+            "Send {} eGLD\n  from:     {}\n  to:       {}\n  nonce:    {}\n  max fee:  {} eGLD ({} gas at {})\n  chain ID: {}",
+            self.value()?.to_human(),
+            self.sender,
+            self.receiver,
+            self.nonce,
+            self.max_fee()?.to_human(),
+            self.gas_limit,
+            self.gas_price,
+            self.chain_id
+        ) + &self.summary_extras()?)
+    }
+    /// Optional lines of the summary
+    fn summary_extras(&self) -> Result<String> {
+        let mut extras = String::new();
+        let data = self.data()?;
+        if !data.is_empty() {
+            extras += &format!("\n  data:     {}", String::from_utf8_lossy(&data));
+        }
+        if let Some(guardian) = &self.guardian {
+            extras += &format!("\n  guardian: {}", guardian);
+        }
+        if let Some(relayer) = &self.relayer {
+            extras += &format!("\n  relayer:  {} (pays the fee)", relayer);
+        }
+        Ok(extras)
+    }
+    /// Sign the transaction with a `Signer`, e.g., an `Account`, to produce a `SignedTransaction`
+    pub fn sign<S: Signer + ?Sized>(&self, signer: &S) -> Result<SignedTransaction> {
+        let signature = hex::encode(signer.sign_bytes(&self.signing_bytes()?)?);
         Ok(SignedTransaction {
             nonce: self.nonce,
             value: self.value.clone(),
             receiver: self.receiver.clone(),
             sender: self.sender.clone(),
+            sender_username: self.sender_username.clone(),
+            receiver_username: self.receiver_username.clone(),
             gas_price: self.gas_price,
             gas_limit: self.gas_limit,
-            chain_ID: self.chain_ID.clone(),
+            chain_id: self.chain_id.clone(),
             version: self.version,
             // signed transaction requires empty data field if no data
-            data: "".to_string(),
-            signature
+            data: self.data.clone(),
+            options: self.options,
+            guardian: self.guardian.clone(),
+            relayer: self.relayer.clone(),
+            signature,
+            guardian_signature: None,
+            relayer_signature: None
         })
     }
 }
 
 /// Representation of a signed transaction. Differs from unsigned transaction only by the
 /// addition of a signature field
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")] 
 pub struct SignedTransaction{
     nonce: u64,
     value: String,
     receiver: String,
     sender: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    sender_username: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    receiver_username: String,
     gas_price: u64,
     gas_limit: u64,
     data: String,
-    // 'chain_ID' needs to be weirdly cased due to requirements of Elrond API
-    chain_ID: String,
+    // 'chainID' needs to be weirdly cased due to requirements of Elrond API
+    #[serde(rename = "chainID")]
+    chain_id: String,
     version: u64,
-    signature: String
+    #[serde(default, skip_serializing_if = "is_zero")]
+    options: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    guardian: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    relayer: Option<String>,
+    signature: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    guardian_signature: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    relayer_signature: Option<String>
 }
 
 impl SignedTransaction {
+    /// The transaction as it was before signing
+    pub fn unsigned(&self) -> UnsignedTransaction {
+        UnsignedTransaction {
+            nonce: self.nonce,
+            value: self.value.clone(),
+            receiver: self.receiver.clone(),
+            sender: self.sender.clone(),
+            sender_username: self.sender_username.clone(),
+            receiver_username: self.receiver_username.clone(),
+            gas_price: self.gas_price,
+            gas_limit: self.gas_limit,
+            data: self.data.clone(),
+            chain_id: self.chain_id.clone(),
+            version: self.version,
+            options: self.options,
+            guardian: self.guardian.clone(),
+            relayer: self.relayer.clone()
+        }
+    }
+    /// Hex encoded signature of the transaction
+    pub fn signature(&self) -> &str {
+        &self.signature
+    }
+    /// Check that the signature was produced by the sender of the transaction, and the guardian
+    /// and relayer signatures (if any) by the guardian and relayer
+    pub fn verify(&self) -> Result<()> {
+        let signing_bytes = self.unsigned().signing_bytes()?;
+        verify_signature(&ElrondAddress::new(&self.sender)?, &signing_bytes, &self.signature)?;
+        verify_co_signature("guardian", &self.guardian, &self.guardian_signature, &signing_bytes)?;
+        verify_co_signature("relayer", &self.relayer, &self.relayer_signature, &signing_bytes)
+    }
+    /// Hex encoded signature of the guardian, if any
+    pub fn guardian_signature(&self) -> Option<&str> {
+        self.guardian_signature.as_deref()
+    }
+    /// Co-sign a guarded transaction as the guardian of the sender, after the sender signed it
+    pub fn sign_as_guardian<S: Signer + ?Sized>(mut self, guardian: &S) -> Result<Self> {
+        if self.guardian.as_deref() != Some(guardian.address().to_string().as_str()) {
+            return Err(ElrondClientError::new("transaction is not guarded by the signing account"));
+        }
+        self.guardian_signature = Some(hex::encode(guardian.sign_bytes(&self.unsigned().signing_bytes()?)?));
+        Ok(self)
+    }
+    /// Hex encoded signature of the relayer, if any
+    pub fn relayer_signature(&self) -> Option<&str> {
+        self.relayer_signature.as_deref()
+    }
+    /// Co-sign a relayed (v3) transaction as its relayer, after the sender signed it
+    pub fn sign_as_relayer<S: Signer + ?Sized>(mut self, relayer: &S) -> Result<Self> {
+        if self.relayer.as_deref() != Some(relayer.address().to_string().as_str()) {
+            return Err(ElrondClientError::new("transaction is not relayed by the signing account"));
+        }
+        self.relayer_signature = Some(hex::encode(relayer.sign_bytes(&self.unsigned().signing_bytes()?)?));
+        Ok(self)
+    }
     pub fn serialize(&self) -> Result<String> {
         serde_json::to_string(self).map_err(|_| {
             ElrondClientError::new("could not serialize signed transaction")
@@ -143,8 +444,10 @@ impl SignedTransaction {
 
 #[cfg(test)]
 mod tests {
-    use super::{UnsignedTransaction, Network, ElrondCurrencyAmount};
+    use sha3::{Digest, Keccak256};
+    use super::{UnsignedTransaction, Network, ElrondCurrencyAmount, OPTION_HASH_SIGN};
     use super::super::account::Account;
+    use super::super::Signer;
     #[test]
     fn create_serialize_and_sign_tx(){
         let private_key = "a4b36a5d97176618b5a7fcc9228d2fd98ee2f14ddd3d6462ae03e40eb487d15b";
@@ -161,9 +464,81 @@ mod tests {
         assert_eq!(serialized_correct, signed_tx.serialize().unwrap());
     }
 
+    const ALICE_SECRET: &str = "413f42575f7f26fad3317a778771212fdb80245850981e48b58a4f25e344e8f9";
+    const ALICE: &str = "erd1qyu5wthldzr8wx5c9ucg8kjagg0jfs53s8nr3zpz3hypefsdd8ssycr6th";
+    const BOB: &str = "erd1spyavw0956vq68xj8y4tenjpq2wd5a9p2c6j8gsz7ztyrnpxrruqzu66jx";
+    const CAROL: &str = "erd1k2s324ww2g0yj38qn2ch2jwctdy8mnfxep94q9arncc6xecg3xaq6mjse8";
+
+    fn testnet() -> Network {
+        Network::Custom("local-testnet".to_string())
+    }
+
+    #[test]
+    fn reference_signatures() {
+        let alice = Account::from_string(ALICE_SECRET).unwrap();
+        assert_eq!(alice.address.to_string(), ALICE);
+        let tx = UnsignedTransaction::new(89, "0", BOB, ALICE, testnet()).unwrap();
+        assert_eq!(
+            tx.sign(&alice).unwrap().signature(),
+            "b56769014f2bdc5cf9fc4a05356807d71fcf8775c819b0f1b0964625b679c918ffa64862313bfef86f99b38cb84fcdb16fa33ad6eb565276616723405cd8f109"
+        );
+    }
+
+    #[test]
+    fn field_order_and_omission(){
+        let tx = UnsignedTransaction::new(89, "0", BOB, ALICE, Network::Custom("D".to_string()))
+            .unwrap()
+            .with_version(2);
+        assert_eq!(
+            tx.serialize().unwrap(),
+            format!("{{\"nonce\":89,\"value\":\"0\",\"receiver\":\"{}\",\"sender\":\"{}\",\"gasPrice\":1000000000,\"gasLimit\":50000,\"chainID\":\"D\",\"version\":2}}", BOB, ALICE)
+        );
+        let tx = UnsignedTransaction::new(204, "1", ALICE, CAROL, Network::Custom("T".to_string()))
+            .unwrap()
+            .with_sender_username("carol")
+            .with_receiver_username("alice")
+            .with_data(b"hello")
+            .with_guardian(BOB)
+            .unwrap();
+        assert_eq!(
+            tx.serialize().unwrap(),
+            format!("{{\"nonce\":204,\"value\":\"1000000000000000000\",\"receiver\":\"{}\",\"sender\":\"{}\",\"senderUsername\":\"Y2Fyb2w=\",\"receiverUsername\":\"YWxpY2U=\",\"gasPrice\":1000000000,\"gasLimit\":107500,\"data\":\"aGVsbG8=\",\"chainID\":\"T\",\"version\":2,\"options\":2,\"guardian\":\"{}\"}}", ALICE, CAROL, BOB)
+        );
+    }
+
+    #[test]
+    fn data_keeps_extra_gas(){
+        let tx = UnsignedTransaction::new(1, "0", BOB, ALICE, testnet())
+            .unwrap()
+            .with_guardian(CAROL)
+            .unwrap()
+            .with_relayer(CAROL)
+            .unwrap()
+            .with_data(b"hello");
+        assert_eq!(tx.gas_limit(), 50000 + 50000 + 50000 + 1500 * 5);
+        // replacing the data replaces its cost
+        let tx = tx.with_data(b"hi");
+        assert_eq!(tx.gas_limit(), 50000 + 50000 + 50000 + 1500 * 2);
+    }
+
+    #[test]
+    fn hash_signing(){
+        let alice = Account::from_string(ALICE_SECRET).unwrap();
+        let tx = UnsignedTransaction::new(89, "0", BOB, ALICE, testnet()).unwrap().with_hash_signing();
+        assert!(tx.serialize().unwrap().ends_with("\"version\":2,\"options\":1}"));
+        let hash = Keccak256::digest(tx.serialize().unwrap().as_bytes()).to_vec();
+        assert_eq!(tx.signing_bytes().unwrap(), hash);
+        let signed = tx.sign(&alice).unwrap();
+        assert_eq!(signed.signature(), alice.sign_bytes(&hash).map(hex::encode).unwrap());
+        assert!(signed.verify().is_ok());
+        // options are only valid from version 2
+        let tx = UnsignedTransaction::new(89, "0", BOB, ALICE, testnet()).unwrap().with_options(OPTION_HASH_SIGN);
+        assert!(tx.sign(&alice).is_err());
+    }
+
     #[test]
     fn test_currency_precision(){
         let amount = ElrondCurrencyAmount::new("0.001").unwrap();
         assert_eq!(amount.to_string(), "1000000000000000")
     }
-}
\ No newline at end of file
+}